@@ -1,17 +1,22 @@
+use crate::qis::density::DensityMatrixBuilder;
+use crate::qis::state::StateVectorBuilder;
 use crate::qis::unitary::UnitaryBuilder;
-use super::gates::{Gate, Gradient, Unitary};
-use super::Operation;
+use super::gates::{Gate, Gradient, Hessian, Unitary};
+use super::noise::superoperator_to_choi;
+use super::{Operation, ParameterMap};
 use itertools::Itertools;
 
 use itertools::izip;
-use ndarray::{Array2, Array3, ArrayD, ArrayView2, Ix2, Array1};
+use ndarray::{s, Array2, Array3, Array4, ArrayD, ArrayView1, ArrayView2, Axis, Ix2, Array1};
 use ndarray_linalg::c64;
+use rand::Rng;
 use crate::squaremat::*;
+use crate::utils::state_dot;
 use crate::permutation_matrix::calc_permutation_matrix;
 
 type Cycle = usize;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Circuit {
     pub size: usize,
     pub radixes: Vec<usize>,
@@ -21,6 +26,8 @@ pub struct Circuit {
     pub num_params: usize,
     pub sendable: bool,
     pub dim: usize,
+    pub param_map: Option<ParameterMap>,
+    pub variables: Vec<f64>,
 }
 
 impl Circuit {
@@ -36,8 +43,10 @@ impl Circuit {
         let mut current_cycle = 0usize;
         for (cycle, op) in &ops_with_cycles {
             num_params += op.gate.num_params();
-            if let Gate::Dynamic(_) = op.gate {
-                sendable = false
+            match &op.gate {
+                Gate::Dynamic(_) => sendable = false,
+                Gate::Circuit(c) => sendable &= c.is_sendable(),
+                _ => (),
             }
             if *cycle != current_cycle {
                 cycle_boundaries.push((current_cycle, *cycle));
@@ -54,6 +63,8 @@ impl Circuit {
             num_params,
             sendable,
             dim,
+            param_map: None,
+            variables: vec![],
         }
     }
 
@@ -61,7 +72,36 @@ impl Circuit {
         self.sendable
     }
 
+    /// Drive the operations' parameters from shared free variables.
+    ///
+    /// Afterwards the circuit's parameters are the map's variables, so
+    /// `num_params`, `set_params` and the gradients all refer to them.
+    pub fn set_param_map(&mut self, param_map: ParameterMap, variables: &[f64]) {
+        let num_op_params: usize = self.ops.iter().map(|op| op.num_params()).sum();
+        if param_map.num_outputs() != num_op_params {
+            panic!(
+                "Parameter map produces {} parameters, but the operations take {}",
+                param_map.num_outputs(),
+                num_op_params
+            );
+        }
+        self.num_params = param_map.num_vars();
+        self.param_map = Some(param_map);
+        self.set_params(variables);
+    }
+
+    /// Convert circuit parameters into the concatenated parameters of the operations.
+    fn expand_params(&self, params: &[f64]) -> Vec<f64> {
+        match &self.param_map {
+            Some(map) if !params.is_empty() => map.expand(params),
+            _ => params.to_vec(),
+        }
+    }
+
     pub fn get_params(&self) -> Vec<f64> {
+        if self.param_map.is_some() {
+            return self.variables.clone();
+        }
         let ret = Vec::with_capacity(self.num_params());
         self.ops.iter().fold(ret, |mut ret, op| {
             ret.extend_from_slice(&op.params);
@@ -77,6 +117,13 @@ impl Circuit {
                 params.len()
             );
         }
+        let params = match &self.param_map {
+            Some(map) => {
+                self.variables = params.to_vec();
+                map.expand(params)
+            }
+            None => params.to_vec(),
+        };
         let mut param_idx = 0;
         for op in self.ops.iter_mut() {
             let parameters = &params[param_idx..param_idx + op.num_params()];
@@ -85,28 +132,279 @@ impl Circuit {
         }
     }
 
+    /// Simulate the circuit on `|0...0>` one operation at a time, without
+    /// building its unitary.
     pub fn get_state(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array1<c64> {
-        let mut zero = Array1::zeros((self.dim,));
-        zero[0] = c64::new(1.0, 0.0);
-        self.get_utry(params, const_gates).dot(&zero.view())
-        // TODO: Calculate state via Matrix-Vector vs Matrix-Matrix mul
+        let params = &self.expand_params(params)[..];
+        let mut state = StateVectorBuilder::new(self.radixes.clone());
+        let mut param_idx = 0;
+        for op in &self.ops {
+            let op_params = if params.is_empty() {
+                &[]
+            } else {
+                &params[param_idx..param_idx + op.num_params()]
+            };
+            op.apply_state(&mut state, op_params, const_gates);
+            param_idx += op.num_params();
+        }
+        state.get_state()
     }
 
+    /// Simulate one quantum trajectory of the noisy circuit on `|0...0>`.
+    ///
+    /// After each operation, every one of its channels applies a single Kraus
+    /// operator `K_k`, drawn with probability `||K_k ψ||^2`, and renormalizes
+    /// the state. Averaging over trajectories reproduces `get_density_matrix`.
+    pub fn sample_trajectory<R: Rng>(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        rng: &mut R,
+    ) -> Array1<c64> {
+        let params = &self.expand_params(params)[..];
+        let mut state = StateVectorBuilder::new(self.radixes.clone());
+        let mut param_idx = 0;
+        for op in &self.ops {
+            let op_params = if params.is_empty() {
+                &[]
+            } else {
+                &params[param_idx..param_idx + op.num_params()]
+            };
+            op.apply_state(&mut state, op_params, const_gates);
+            for channel in &op.noise {
+                state.apply_kraus(channel, &op.location, rng.gen());
+            }
+            param_idx += op.num_params();
+        }
+        state.get_state()
+    }
+
+    /// Simulate the circuit on `|0...0>` along with the derivative of the
+    /// state with respect to each parameter.
+    ///
+    /// Each derivative is carried forward as its own state vector, so memory
+    /// grows as `num_params * dim` rather than `num_params * dim^2`.
     pub fn get_state_and_grads(&self, params: &[f64], const_gates: &[Array2<c64>]) -> (Array1<c64>, Array2<c64>) {
-        let mut zero = Array1::zeros((self.dim,));
-        zero[0] = c64::new(1.0, 0.0);
-        let (utry, grads) = self.get_utry_and_grad(params, const_gates);
-        let state = utry.dot(&zero.view());
-        
-        let mut out_grad = Array2::zeros((
-            params.len(),
-            self.dim,
-        ));
+        let op_params = &self.expand_params(params)[..];
+        let mut state = StateVectorBuilder::new(self.radixes.clone());
+        let mut d_states: Vec<StateVectorBuilder> = vec![];
+        let mut param_idx = 0;
+        for op in &self.ops {
+            let params = if op_params.is_empty() {
+                &[]
+            } else {
+                &op_params[param_idx..param_idx + op.num_params()]
+            };
+            let (utry, grad) = op.get_utry_and_grad(params, const_gates);
+            for d_state in d_states.iter_mut() {
+                d_state.apply(utry.view(), &op.location);
+            }
+            for d_utry in grad.outer_iter() {
+                let mut d_state = state.clone();
+                d_state.apply(d_utry, &op.location);
+                d_states.push(d_state);
+            }
+            state.apply(utry.view(), &op.location);
+            param_idx += op.num_params();
+        }
 
-        for (mut arr, grad) in out_grad.outer_iter_mut().zip(grads.outer_iter()) {
-            arr.assign(&grad.dot(&zero.view()));
+        let mut out_grad = Array2::zeros((d_states.len(), self.dim));
+        for (mut arr, d_state) in out_grad.outer_iter_mut().zip(&d_states) {
+            arr.assign(&d_state.get_state());
+        }
+        match &self.param_map {
+            Some(map) => (state.get_state(), map.reduce_grad(out_grad.view())),
+            None => (state.get_state(), out_grad),
         }
-        (state, out_grad)
+    }
+
+    /// Calculate the state together with `<bra|∂ψ/∂θ_k>` for every parameter,
+    /// where `|ψ>` is the circuit applied to `|0...0>`.
+    ///
+    /// This is the adjoint method: after simulating forward, the operations are
+    /// undone in reverse with one bra and one ket, so memory stays `O(dim)` and
+    /// time is `O(num_ops)` state vector applications.
+    pub fn get_state_and_adjoint_grads(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        bra: ArrayView1<c64>,
+    ) -> (Array1<c64>, Array1<c64>) {
+        let op_params = &self.expand_params(params)[..];
+        let op_ranges: Vec<(usize, usize)> = self
+            .ops
+            .iter()
+            .scan(0, |param_idx, op| {
+                let start = *param_idx;
+                *param_idx += op.num_params();
+                Some((start, *param_idx))
+            })
+            .collect();
+        let params_of = |(start, end): (usize, usize)| {
+            if op_params.is_empty() {
+                &[][..]
+            } else {
+                &op_params[start..end]
+            }
+        };
+
+        let mut ket = StateVectorBuilder::new(self.radixes.clone());
+        for (op, &range) in self.ops.iter().zip(&op_ranges) {
+            op.apply_state(&mut ket, params_of(range), const_gates);
+        }
+        let state = ket.get_state();
+
+        let mut bra = StateVectorBuilder::from_state(self.radixes.clone(), bra.to_owned());
+        let num_op_params = op_ranges.last().map_or(0, |&(_, end)| end);
+        let mut out_grad = Array1::zeros(num_op_params);
+        for (op, &range) in self.ops.iter().zip(&op_ranges).rev() {
+            let (utry, grad) = op.get_utry_and_grad(params_of(range), const_gates);
+            let utry_dagger = utry.t().mapv(|x| x.conj());
+            ket.apply(utry_dagger.view(), &op.location);
+            let bra_state = bra.get_state();
+            for (k, d_utry) in grad.outer_iter().enumerate() {
+                let mut d_ket = ket.clone();
+                d_ket.apply(d_utry, &op.location);
+                out_grad[range.0 + k] = state_dot(bra_state.view(), d_ket.get_state().view());
+            }
+            bra.apply(utry_dagger.view(), &op.location);
+        }
+        match &self.param_map {
+            Some(map) => (state, map.reduce_grad(out_grad.view())),
+            None => (state, out_grad),
+        }
+    }
+
+    /// Calculate `Tr(T^† U)` together with `Tr(T^† ∂U/∂θ_k)` for every
+    /// parameter, where `T` is `target` and `U` is the circuit's unitary.
+    ///
+    /// The target is folded into a single `UnitaryBuilder` holding `U T^†`.
+    /// Sweeping backwards, each operation is moved from the left of that
+    /// product to the right, so the tensor always holds the environment of the
+    /// current operation and each gradient entry is a trace against it. No
+    /// `(num_params, dim, dim)` gradient is ever formed.
+    pub fn get_target_trace_and_grads(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        target: ArrayView2<c64>,
+    ) -> (c64, Array1<c64>) {
+        let op_params = &self.expand_params(params)[..];
+        let op_ranges: Vec<(usize, usize)> = self
+            .ops
+            .iter()
+            .scan(0, |param_idx, op| {
+                let start = *param_idx;
+                *param_idx += op.num_params();
+                Some((start, *param_idx))
+            })
+            .collect();
+        let params_of = |(start, end): (usize, usize)| {
+            if op_params.is_empty() {
+                &[][..]
+            } else {
+                &op_params[start..end]
+            }
+        };
+
+        let location: Vec<usize> = (0..self.size).collect();
+        let mut builder = UnitaryBuilder::new(self.size, self.radixes.clone());
+        builder.apply_right(target, &location, true);
+        for (op, &range) in self.ops.iter().zip(&op_ranges) {
+            op.apply_right(&mut builder, params_of(range), const_gates, false);
+        }
+        let trace = builder.get_utry().diag().sum();
+
+        let num_op_params = op_ranges.last().map_or(0, |&(_, end)| end);
+        let mut out_grad = Array1::zeros(num_op_params);
+        for (op, &range) in self.ops.iter().zip(&op_ranges).rev() {
+            op.apply_right(&mut builder, params_of(range), const_gates, true);
+            if op.num_params() != 0 {
+                let env = builder.calc_env_matrix(&op.location);
+                let grad = op.get_grad(params_of(range), const_gates);
+                for (k, d_utry) in grad.outer_iter().enumerate() {
+                    out_grad[range.0 + k] = (&env * &d_utry.t()).sum();
+                }
+            }
+            op.apply_left(&mut builder, params_of(range), const_gates, false);
+        }
+        match &self.param_map {
+            Some(map) => (trace, map.reduce_grad(out_grad.view())),
+            None => (trace, out_grad),
+        }
+    }
+
+    /// Simulate the circuit, including the noise on its operations, on the
+    /// density matrix `|0...0><0...0|`.
+    ///
+    /// Noise on operations inside a `CircuitGate` is not simulated, since that
+    /// gate is applied through its unitary.
+    pub fn get_density_matrix(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let mut builder = DensityMatrixBuilder::new(self.radixes.clone());
+        self.apply_density(&mut builder, params, const_gates);
+        builder.get_density_matrix()
+    }
+
+    /// Simulate the circuit, including the noise on its operations, on the
+    /// density matrix `rho`.
+    pub fn evolve_density_matrix(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        rho: ArrayView2<c64>,
+    ) -> Array2<c64> {
+        let mut builder = DensityMatrixBuilder::from_density_matrix(self.radixes.clone(), rho.to_owned());
+        self.apply_density(&mut builder, params, const_gates);
+        builder.get_density_matrix()
+    }
+
+    fn apply_density(&self, builder: &mut DensityMatrixBuilder, params: &[f64], const_gates: &[Array2<c64>]) {
+        let params = &self.expand_params(params)[..];
+        let mut param_idx = 0;
+        for op in &self.ops {
+            let op_params = if params.is_empty() {
+                &[]
+            } else {
+                &params[param_idx..param_idx + op.num_params()]
+            };
+            op.apply_density(builder, op_params, const_gates);
+            param_idx += op.num_params();
+        }
+    }
+
+    /// Calculate the superoperator of the noisy circuit, acting on row-major
+    /// vectorized density matrices.
+    ///
+    /// This is built like a unitary on two copies of the circuit's qudits: each
+    /// gate `U` is applied as `U` to the first copy and `conj(U)` to the second,
+    /// and each channel's superoperator to both copies of its location.
+    pub fn get_superoperator(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let params = &self.expand_params(params)[..];
+        let radixes = [&self.radixes[..], &self.radixes[..]].concat();
+        let mut builder = UnitaryBuilder::new(2 * self.size, radixes);
+        let mut param_idx = 0;
+        for op in &self.ops {
+            let op_params = if params.is_empty() {
+                &[]
+            } else {
+                &params[param_idx..param_idx + op.num_params()]
+            };
+            let utry = op.get_utry(op_params, const_gates);
+            let mirrored: Vec<usize> = op.location.iter().map(|q| q + self.size).collect();
+            builder.apply_right(utry.view(), &op.location, false);
+            builder.apply_right(utry.mapv(|x| x.conj()).view(), &mirrored, false);
+            let both = [&op.location[..], &mirrored[..]].concat();
+            for channel in &op.noise {
+                builder.apply_right(channel.superoperator().view(), &both, false);
+            }
+            param_idx += op.num_params();
+        }
+        builder.get_utry()
+    }
+
+    /// Calculate the Choi matrix `Σ_ij |i><j| ⊗ E(|i><j|)` of the noisy circuit.
+    pub fn get_choi(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        superoperator_to_choi(self.get_superoperator(params, const_gates).view())
     }
 }
 
@@ -116,21 +414,20 @@ impl Unitary for Circuit {
     }
 
     fn get_utry(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let params = &self.expand_params(params)[..];
         if !params.is_empty() {
             let mut param_idx = 0;
             let mut builder = UnitaryBuilder::new(self.size, self.radixes.clone());
             for op in &self.ops {
-                let utry = op
-                    .get_utry(&params[param_idx..param_idx + op.num_params()], const_gates);
+                let op_params = &params[param_idx..param_idx + op.num_params()];
+                op.apply_right(&mut builder, op_params, const_gates, false);
                 param_idx += op.num_params();
-                builder.apply_right(utry.view(), &op.location, false);
             }
             builder.get_utry()
         } else {
             let mut builder = UnitaryBuilder::new(self.size, self.radixes.clone());
             for op in &self.ops {
-                let utry = op.get_utry(&[], const_gates);
-                builder.apply_right(utry.view(), &op.location, false);
+                op.apply_right(&mut builder, &[], const_gates, false);
             }
             builder.get_utry()
         }
@@ -138,19 +435,14 @@ impl Unitary for Circuit {
 }
 
 
-impl Gradient for Circuit {
-    fn get_utry_and_grad(
+impl Circuit {
+    /// Calculate the unitary and its gradient with respect to the concatenated
+    /// parameters of the operations.
+    fn get_op_utry_and_grad(
         &self,
         params: &[f64],
         const_gates: &[Array2<c64>],
     ) -> (Array2<c64>, Array3<c64>) {
-        if params.len() != self.num_params() {
-            panic!(
-                "Incorrect number of params passed to circuit, expected {}, got {}",
-                self.num_params(),
-                params.len()
-            );
-        }
         let mut matrices = vec![];
         let mut grads = vec![];
         let mut locations = vec![];
@@ -247,6 +539,123 @@ impl Gradient for Circuit {
 
         (left.get_utry(), out_grad)
     }
+}
+
+impl Circuit {
+    /// Calculate the unitary, its gradient and its Hessian with respect to the
+    /// concatenated parameters of the operations.
+    ///
+    /// This mirrors the sweep in `get_op_utry_and_grad`, but keeps each first
+    /// derivative propagated through the later operations so that it can be
+    /// paired with their derivatives.
+    fn get_op_utry_grad_and_hessian(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>, Array4<c64>) {
+        let mut derivatives = vec![];
+        let mut num_grads = 0usize;
+        let mut param_idx = 0;
+        for op in &self.ops {
+            let op_params = if params.is_empty() {
+                &[]
+            } else {
+                &params[param_idx..param_idx + op.num_params()]
+            };
+            let (utry, grad, hess) = op.get_utry_grad_and_hessian(op_params, const_gates);
+            num_grads += grad.shape()[0];
+            param_idx += op.num_params();
+            derivatives.push((utry, grad, hess));
+        }
+
+        // Expand a gate's matrix to the full circuit
+        let embed = |m: ArrayView2<c64>, location: &[usize]| {
+            let mut builder = UnitaryBuilder::new(self.size, self.radixes.clone());
+            builder.apply_right(m, location, false);
+            builder.get_utry()
+        };
+
+        let mut left = UnitaryBuilder::new(self.size, self.radixes.clone());
+        let mut right = UnitaryBuilder::new(self.size, self.radixes.clone());
+        let mut out_grad = Array3::zeros((num_grads, self.dim, self.dim));
+        let mut out_hess = Array4::zeros((num_grads, num_grads, self.dim, self.dim));
+
+        for (op, (m, _, _)) in self.ops.iter().zip(&derivatives) {
+            right.apply_right(m.view(), &op.location, false);
+        }
+
+        // Derivatives of the earlier operations, carried up to the current one
+        let mut partials: Vec<Array2<c64>> = Vec::with_capacity(num_grads);
+        let mut grad_idx = 0;
+        for (op, (m, d_m, dd_m)) in self.ops.iter().zip(&derivatives) {
+            // Remove the gate from the right environment
+            right.apply_left(m.view(), &op.location, true);
+            let right_utry = right.get_utry();
+            let left_utry = left.get_utry();
+
+            let full_grads: Vec<Array2<c64>> = d_m
+                .outer_iter()
+                .map(|grad| right_utry.dot(&embed(grad, &op.location)))
+                .collect();
+            for (k, full_grad) in full_grads.iter().enumerate() {
+                out_grad
+                    .index_axis_mut(Axis(0), grad_idx + k)
+                    .assign(&full_grad.dot(&left_utry));
+                for (l, hess) in dd_m.index_axis(Axis(0), k).outer_iter().enumerate() {
+                    let full_hess = right_utry.dot(&embed(hess, &op.location)).dot(&left_utry);
+                    out_hess
+                        .slice_mut(s![grad_idx + k, grad_idx + l, .., ..])
+                        .assign(&full_hess);
+                }
+                for (p, partial) in partials.iter().enumerate() {
+                    let full_hess = full_grad.dot(partial);
+                    out_hess
+                        .slice_mut(s![p, grad_idx + k, .., ..])
+                        .assign(&full_hess);
+                    out_hess
+                        .slice_mut(s![grad_idx + k, p, .., ..])
+                        .assign(&full_hess);
+                }
+            }
+
+            // Carry the earlier derivatives past the gate and add its own
+            let full_utry = embed(m.view(), &op.location);
+            for partial in partials.iter_mut() {
+                *partial = full_utry.dot(partial);
+            }
+            for grad in d_m.outer_iter() {
+                partials.push(embed(grad, &op.location).dot(&left_utry));
+            }
+            left.apply_right(m.view(), &op.location, false);
+            grad_idx += d_m.shape()[0];
+        }
+
+        (left.get_utry(), out_grad, out_hess)
+    }
+}
+
+impl Gradient for Circuit {
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        if params.len() != self.num_params() {
+            panic!(
+                "Incorrect number of params passed to circuit, expected {}, got {}",
+                self.num_params(),
+                params.len()
+            );
+        }
+        match &self.param_map {
+            Some(map) => {
+                let op_params = self.expand_params(params);
+                let (utry, grad) = self.get_op_utry_and_grad(&op_params, const_gates);
+                (utry, map.reduce_grad(grad.view()))
+            }
+            None => self.get_op_utry_and_grad(params, const_gates),
+        }
+    }
 
     fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
         if params.len() != self.num_params() {
@@ -258,4 +667,220 @@ impl Gradient for Circuit {
         }
         self.get_utry_and_grad(params, const_gates).1
     }
-}
\ No newline at end of file
+}
+
+impl Hessian for Circuit {
+    fn get_utry_grad_and_hessian(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>, Array4<c64>) {
+        if params.len() != self.num_params() {
+            panic!(
+                "Incorrect number of params passed to circuit, expected {}, got {}",
+                self.num_params(),
+                params.len()
+            );
+        }
+        match &self.param_map {
+            Some(map) => {
+                let op_params = self.expand_params(params);
+                let (utry, grad, hess) = self.get_op_utry_grad_and_hessian(&op_params, const_gates);
+                (
+                    utry,
+                    map.reduce_grad(grad.view()),
+                    map.reduce_hessian(hess.view()),
+                )
+            }
+            None => self.get_op_utry_grad_and_hessian(params, const_gates),
+        }
+    }
+
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        self.get_utry_grad_and_hessian(params, const_gates).2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+    use crate::ir::gates::{DiagonalGate, PermutationGate, SpecialUnitaryGate, U3Gate};
+    use crate::ir::{AffineExpression, KrausChannel};
+    use crate::permutation_matrix::Permutation;
+
+    fn max_diff(a: ArrayView1<c64>, b: ArrayView1<c64>) -> f64 {
+        (&a - &b).iter().map(|x| x.norm()).fold(0.0, f64::max)
+    }
+
+    fn max_diff_2d(a: ArrayView2<c64>, b: ArrayView2<c64>) -> f64 {
+        (&a - &b).iter().map(|x| x.norm()).fold(0.0, f64::max)
+    }
+
+    /// A mixed-radix circuit that exercises the diagonal and permutation fast paths.
+    fn mixed_radix_circuit() -> Circuit {
+        let mut rng = rng(21);
+        let ops = vec![
+            (
+                0,
+                Operation::new(U3Gate::new().into(), vec![0], random_params(3, &mut rng)),
+            ),
+            (
+                0,
+                Operation::new(
+                    SpecialUnitaryGate::new(3).into(),
+                    vec![1],
+                    random_params(8, &mut rng),
+                ),
+            ),
+            (
+                1,
+                Operation::new(
+                    DiagonalGate::new(2, vec![3, 2]).into(),
+                    vec![1, 2],
+                    random_params(6, &mut rng),
+                ),
+            ),
+            (
+                2,
+                Operation::new(
+                    PermutationGate::new(Permutation::new(vec![1, 0]), vec![2, 2]).into(),
+                    vec![2, 0],
+                    vec![],
+                ),
+            ),
+            (
+                3,
+                Operation::new(U3Gate::new().into(), vec![2], random_params(3, &mut rng)),
+            ),
+        ];
+        Circuit::new(3, vec![2, 3, 2], ops, vec![])
+    }
+
+    /// `mixed_radix_circuit` with its parameters driven by three shared variables.
+    fn mapped_circuit() -> Circuit {
+        let mut circ = mixed_radix_circuit();
+        let exprs = (0..circ.num_params())
+            .map(|i| {
+                AffineExpression::new(
+                    vec![(i % 3, 0.5 + 0.1 * i as f64), ((i + 1) % 3, 0.3)],
+                    -0.2,
+                )
+            })
+            .collect();
+        circ.set_param_map(ParameterMap::new(3, exprs), &[0.4, -1.1, 2.3]);
+        circ
+    }
+
+    #[test]
+    fn state_is_first_column_of_unitary() {
+        let circ = mixed_radix_circuit();
+        let params = circ.get_params();
+        let utry = circ.get_utry(&params, &[]);
+        let state = circ.get_state(&params, &[]);
+        assert!(max_diff(state.view(), utry.column(0)) < 1e-12);
+    }
+
+    #[test]
+    fn state_grads_are_first_column_of_unitary_grads() {
+        let circ = mixed_radix_circuit();
+        let params = circ.get_params();
+        let (utry, utry_grads) = circ.get_utry_and_grad(&params, &[]);
+        let (state, state_grads) = circ.get_state_and_grads(&params, &[]);
+        assert!(max_diff(state.view(), utry.column(0)) < 1e-12);
+        assert_eq!(state_grads.nrows(), params.len());
+        for (row, grad) in state_grads.outer_iter().zip(utry_grads.outer_iter()) {
+            assert!(max_diff(row, grad.column(0)) < 1e-12);
+        }
+    }
+
+    /// `mixed_radix_circuit` with depolarizing, amplitude damping and dephasing
+    /// noise after its operations.
+    fn noisy_circuit() -> Circuit {
+        let circ = mixed_radix_circuit();
+        let channels = vec![
+            Some(KrausChannel::amplitude_damping(vec![2], 0.1)),
+            Some(KrausChannel::dephasing(vec![3], 0.2)),
+            Some(KrausChannel::depolarizing(vec![3, 2], 0.05)),
+            None,
+            Some(KrausChannel::amplitude_damping(vec![2], 0.15)),
+        ];
+        let ops = circ
+            .ops
+            .into_iter()
+            .zip(channels)
+            .enumerate()
+            .map(|(cycle, (op, channel))| match channel {
+                Some(channel) => (cycle, op.with_noise(channel)),
+                None => (cycle, op),
+            })
+            .collect();
+        Circuit::new(circ.size, circ.radixes, ops, vec![])
+    }
+
+    #[test]
+    fn noise_free_density_matrix_is_pure_state() {
+        let circ = mixed_radix_circuit();
+        let params = circ.get_params();
+        let state = circ.get_utry(&params, &[]).column(0).to_owned();
+        let pure = Array2::from_shape_fn((circ.dim, circ.dim), |(i, j)| state[i] * state[j].conj());
+        let rho = circ.get_density_matrix(&params, &[]);
+        assert!(max_diff_2d(rho.view(), pure.view()) < 1e-12);
+    }
+
+    #[test]
+    fn superoperator_evolves_ground_state_to_density_matrix() {
+        let circ = noisy_circuit();
+        let params = circ.get_params();
+        let superop = circ.get_superoperator(&params, &[]);
+        // vec(|0><0|) is the first basis vector, so its image is the first column.
+        let evolved = superop
+            .column(0)
+            .to_owned()
+            .into_shape((circ.dim, circ.dim))
+            .unwrap();
+        let rho = circ.get_density_matrix(&params, &[]);
+        assert!(max_diff_2d(evolved.view(), rho.view()) < 1e-12);
+    }
+
+    #[test]
+    fn choi_trace_is_dimension() {
+        let circ = noisy_circuit();
+        let choi = circ.get_choi(&circ.get_params(), &[]);
+        assert!((choi.diag().sum() - c64::new(circ.dim as f64, 0.0)).norm() < 1e-10);
+    }
+
+    #[test]
+    fn adjoint_grads_project_unitary_grads() {
+        let mut rng = rng(22);
+        for circ in [mixed_radix_circuit(), mapped_circuit()] {
+            let params = circ.get_params();
+            let bra = random_env(circ.dim, &mut rng).column(0).to_owned();
+            let (utry, utry_grads) = circ.get_utry_and_grad(&params, &[]);
+            let (state, overlaps) = circ.get_state_and_adjoint_grads(&params, &[], bra.view());
+            assert!(max_diff(state.view(), utry.column(0)) < 1e-12);
+            assert_eq!(overlaps.len(), params.len());
+            for (overlap, grad) in overlaps.iter().zip(utry_grads.outer_iter()) {
+                assert!((overlap - state_dot(bra.view(), grad.column(0))).norm() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn target_trace_grads_match_unitary_grads() {
+        let mut rng = rng(23);
+        for circ in [mixed_radix_circuit(), mapped_circuit()] {
+            let params = circ.get_params();
+            let target = random_env(circ.dim, &mut rng);
+            let target_dagger = target.t().mapv(|x| x.conj());
+            let (utry, utry_grads) = circ.get_utry_and_grad(&params, &[]);
+            let (trace, grads) = circ.get_target_trace_and_grads(&params, &[], target.view());
+            assert!((trace - target_dagger.dot(&utry).diag().sum()).norm() < 1e-12);
+            assert_eq!(grads.len(), params.len());
+            for (grad, utry_grad) in grads.iter().zip(utry_grads.outer_iter()) {
+                let expected = target_dagger.dot(&utry_grad).diag().sum();
+                assert!((grad - expected).norm() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use ndarray::{Array, Array2, ArrayView2, Dimension, Zip};
+use ndarray_linalg::c64;
+
+use super::{finite_difference_grad, Gradient};
+
+/// The discrepancies found by `check_gradient`, each as a largest absolute entry
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct GradientCheck {
+    /// How far the unitary from `get_utry_and_grad` is from `get_utry`
+    pub utry_error: f64,
+    /// How far the gradient from `get_utry_and_grad` is from finite differences
+    pub grad_error: f64,
+    /// How far `get_grad` is from the gradient of `get_utry_and_grad`
+    pub consistency_error: f64,
+    /// How far `U^† U` is from the identity
+    pub unitarity_error: f64,
+}
+
+impl GradientCheck {
+    /// Whether every discrepancy is within `tolerance`.
+    pub fn passed(&self, tolerance: f64) -> bool {
+        self.utry_error <= tolerance
+            && self.grad_error <= tolerance
+            && self.consistency_error <= tolerance
+            && self.unitarity_error <= tolerance
+    }
+}
+
+/// Compare a gate's analytic derivatives against Richardson-extrapolated
+/// central differences of `get_utry`, and check that its unitary is unitary.
+pub fn check_gradient<G: Gradient + ?Sized>(
+    gate: &G,
+    params: &[f64],
+    const_gates: &[Array2<c64>],
+) -> GradientCheck {
+    let (utry, grad) = gate.get_utry_and_grad(params, const_gates);
+    let numerical = finite_difference_grad(gate, params, const_gates);
+    GradientCheck {
+        utry_error: max_abs_diff(&utry, &gate.get_utry(params, const_gates)),
+        grad_error: max_abs_diff(&grad, &numerical),
+        consistency_error: max_abs_diff(&grad, &gate.get_grad(params, const_gates)),
+        unitarity_error: unitarity_error(utry.view()),
+    }
+}
+
+/// The largest absolute entry of `a - b`, or infinity if their shapes differ.
+fn max_abs_diff<D: Dimension>(a: &Array<c64, D>, b: &Array<c64, D>) -> f64 {
+    if a.shape() != b.shape() {
+        return f64::INFINITY;
+    }
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x - y).norm())
+        .fold(0.0, f64::max)
+}
+
+/// The largest absolute entry of `U^† U - I`.
+pub fn unitarity_error(utry: ArrayView2<c64>) -> f64 {
+    let product = utry.t().mapv(|x| x.conj()).dot(&utry);
+    let mut error = 0.0f64;
+    Zip::indexed(&product).for_each(|(i, j), &x| {
+        let expected = if i == j { 1.0 } else { 0.0 };
+        error = error.max((x - c64::new(expected, 0.0)).norm());
+    });
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::ir::circuit::Circuit;
+    use crate::ir::gates::test_utils::*;
+    use crate::ir::gates::utils::rot_x;
+    use crate::ir::gates::*;
+    use crate::ir::Operation;
+    use crate::permutation_matrix::Permutation;
+
+    /// An RX gate without an analytic gradient, standing in for a Python gate
+    #[derive(Debug)]
+    struct NumericRX;
+
+    impl Unitary for NumericRX {
+        fn num_params(&self) -> usize {
+            1
+        }
+
+        fn get_utry(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array2<c64> {
+            rot_x(params[0])
+        }
+    }
+
+    impl Gradient for NumericRX {}
+    impl Hessian for NumericRX {}
+    impl Optimize for NumericRX {}
+    impl DynGate for NumericRX {}
+
+    impl Size for NumericRX {
+        fn num_qudits(&self) -> usize {
+            1
+        }
+    }
+
+    fn mixed_radix_circuit() -> Circuit {
+        let ops = vec![
+            (
+                0,
+                Operation::new(U3Gate::new().into(), vec![0], vec![0.0; 3]),
+            ),
+            (
+                0,
+                Operation::new(SpecialUnitaryGate::new(3).into(), vec![1], vec![0.0; 8]),
+            ),
+            (
+                1,
+                Operation::new(CPhaseGate::new(2, 3).into(), vec![0, 1], vec![0.0]),
+            ),
+            (
+                2,
+                Operation::new(ConstantGate::new(0, 1).into(), vec![1], vec![]),
+            ),
+            (2, Operation::new(RZGate::new().into(), vec![0], vec![0.0])),
+            (
+                3,
+                Operation::new(
+                    DiagonalGate::new(2, vec![3, 2]).into(),
+                    vec![1, 0],
+                    vec![0.0; 6],
+                ),
+            ),
+        ];
+        let shift = Array2::from_shape_fn((3, 3), |(i, j)| {
+            c64::new(if i == (j + 1) % 3 { 1.0 } else { 0.0 }, 0.0)
+        });
+        Circuit::new(2, vec![2, 3], ops, vec![shift])
+    }
+
+    fn hermitian<R: rand::Rng>(dim: usize, rng: &mut R) -> Array2<c64> {
+        let m = random_env(dim, rng);
+        (&m + &m.t().mapv(|x| x.conj())) * c64::new(0.5, 0.0)
+    }
+
+    #[test]
+    fn every_gate_passes() {
+        let mut rng = rng(20);
+        let circuit = mixed_radix_circuit();
+        let generators: Vec<Array2<c64>> = (0..3).map(|_| hermitian(9, &mut rng)).collect();
+        let gates: Vec<(Gate, Vec<Array2<c64>>)> = vec![
+            (
+                ConstantGate::new(0, 1).into(),
+                circuit.constant_gates.clone(),
+            ),
+            (
+                PermutationGate::new(Permutation::new(vec![2, 1, 0]), vec![3, 2, 3]).into(),
+                vec![],
+            ),
+            (U1Gate::new().into(), vec![]),
+            (U2Gate::new().into(), vec![]),
+            (U3Gate::new().into(), vec![]),
+            (U8Gate::new().into(), vec![]),
+            (RXGate::new().into(), vec![]),
+            (RYGate::new().into(), vec![]),
+            (RZGate::new().into(), vec![]),
+            (RXXGate::new().into(), vec![]),
+            (RYYGate::new().into(), vec![]),
+            (RZZGate::new().into(), vec![]),
+            (CRXGate::new().into(), vec![]),
+            (CRYGate::new().into(), vec![]),
+            (CRZGate::new().into(), vec![]),
+            (SU4Gate::new().into(), vec![]),
+            (CSUMGate::new(3).into(), vec![]),
+            (CZGate::new(3).into(), vec![]),
+            (CPhaseGate::new(2, 3).into(), vec![]),
+            (CU3Gate::new().into(), vec![]),
+            (FSimGate::new().into(), vec![]),
+            (PhasedXZGate::new().into(), vec![]),
+            (XXPlusYYGate::new().into(), vec![]),
+            (RXSubGate::new(3, 0, 2).into(), vec![]),
+            (RYSubGate::new(3, 1, 2).into(), vec![]),
+            (RZSubGate::new(4, 3, 1).into(), vec![]),
+            (SpecialUnitaryGate::new(3).into(), vec![]),
+            (PauliRotationGate::new("XYZ").into(), vec![]),
+            (VariableUnitaryGate::new(2, vec![2, 3]).into(), vec![]),
+            (DiagonalGate::new(2, vec![2, 3]).into(), vec![]),
+            (
+                HamiltonianGate::new(2, vec![3, 3], vec![2, 0, 1]).into(),
+                generators,
+            ),
+            (
+                EmbeddedGate::new(
+                    CRYGate::new().into(),
+                    vec![3, 3],
+                    vec![vec![1, 2], vec![2, 0]],
+                )
+                .into(),
+                vec![],
+            ),
+            (
+                ControlledGate::new(
+                    U3Gate::new().into(),
+                    2,
+                    vec![3, 2],
+                    vec![vec![0, 2], vec![1]],
+                )
+                .into(),
+                vec![],
+            ),
+            (DaggerGate::new(U8Gate::new().into()).into(), vec![]),
+            (
+                FrozenParameterGate::new(FSimGate::new().into(), vec![(1, 0.3)]).into(),
+                vec![],
+            ),
+            (CircuitGate::new(circuit.clone()).into(), vec![]),
+            (Gate::Dynamic(Arc::new(NumericRX)), vec![]),
+        ];
+        for (gate, const_gates) in &gates {
+            let params = random_params(gate.num_params(), &mut rng);
+            let check = check_gradient(gate, &params, const_gates);
+            assert!(check.passed(1e-6), "{:?}: {:?}", gate, check);
+        }
+
+        let params = random_params(circuit.num_params(), &mut rng);
+        let check = check_gradient(&circuit, &params, &circuit.constant_gates);
+        assert!(check.passed(1e-6), "{:?}", check);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::ir::circuit::Circuit;
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::ir::inst::QFactorInstantiator;
+
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// A parameterized circuit used as a single gate
+#[derive(Clone, Debug)]
+pub struct CircuitGate {
+    circuit: Box<Circuit>,
+}
+
+impl CircuitGate {
+    pub fn new(circuit: Circuit) -> Self {
+        CircuitGate {
+            circuit: Box::new(circuit),
+        }
+    }
+
+    pub fn is_sendable(&self) -> bool {
+        self.circuit.is_sendable()
+    }
+
+    /// Run one QFactor sweep over the nested circuit starting from `params`.
+    ///
+    /// Maximizing `|Tr(env_matrix @ U)|` is instantiating towards `env_matrix^†`.
+    pub fn optimize_from(&self, params: &[f64], env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let mut circuit = (*self.circuit).clone();
+        circuit.set_params(params);
+        let target =
+            Array2::from_shape_fn(env_matrix.raw_dim(), |(i, j)| env_matrix[[j, i]].conj());
+        let qfactor = QFactorInstantiator::default();
+        let mut unitary_builder = qfactor.initialize_circuit_tensor(&circuit, &target);
+        qfactor.sweep_circuit(&mut unitary_builder, &mut circuit);
+        circuit.get_params()
+    }
+}
+
+impl Unitary for CircuitGate {
+    fn num_params(&self) -> usize {
+        self.circuit.num_params()
+    }
+
+    fn get_utry(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array2<c64> {
+        self.circuit.get_utry(params, &self.circuit.constant_gates)
+    }
+}
+
+impl Gradient for CircuitGate {
+    fn get_grad(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.circuit.get_grad(params, &self.circuit.constant_gates)
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        _const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        self.circuit
+            .get_utry_and_grad(params, &self.circuit.constant_gates)
+    }
+}
+
+impl Hessian for CircuitGate {
+    fn get_hessian(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array4<c64> {
+        self.circuit
+            .get_utry_grad_and_hessian(params, &self.circuit.constant_gates)
+            .2
+    }
+
+    fn get_utry_grad_and_hessian(
+        &self,
+        params: &[f64],
+        _const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>, Array4<c64>) {
+        self.circuit
+            .get_utry_grad_and_hessian(params, &self.circuit.constant_gates)
+    }
+}
+
+impl Size for CircuitGate {
+    fn num_qudits(&self) -> usize {
+        self.circuit.size
+    }
+}
+
+impl Optimize for CircuitGate {
+    /// Sweep starting from the parameters the nested circuit was built with.
+    /// `Operation` warm starts from its current parameters instead.
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        self.optimize_from(&self.circuit.get_params(), env_matrix)
+    }
+
+    fn can_optimize(&self) -> bool {
+        self.circuit
+            .ops
+            .iter()
+            .all(|op| op.num_params() == 0 || op.can_optimize())
+    }
+}
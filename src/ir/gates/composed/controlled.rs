@@ -0,0 +1,189 @@
+use crate::ir::gates::{Gate, Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use super::{can_optimize_block, optimize_block};
+
+use ndarray::{s, Array2, Array3, ArrayView2, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// An arbitrary gate controlled by one or more qudits
+#[derive(Clone, Debug)]
+pub struct ControlledGate {
+    gate: Box<Gate>,
+    control_radixes: Vec<usize>,
+    control_dim: usize,
+    active: Vec<bool>,
+}
+
+impl ControlledGate {
+    /// Control `gate` on `num_controls` qudits placed before it. The gate is
+    /// applied when every control qudit `q` is in one of `control_levels[q]`.
+    pub fn new(
+        gate: Gate,
+        num_controls: usize,
+        control_radixes: Vec<usize>,
+        control_levels: Vec<Vec<usize>>,
+    ) -> Self {
+        assert_eq!(control_radixes.len(), num_controls);
+        assert_eq!(control_levels.len(), num_controls);
+        let control_dim = control_radixes.iter().product();
+        let mut active = vec![true];
+        for (levels, &radix) in control_levels.iter().zip(&control_radixes) {
+            assert!(
+                levels.iter().all(|&level| level < radix),
+                "Control levels {:?} are out of range for radix {}",
+                levels,
+                radix
+            );
+            active = active
+                .iter()
+                .flat_map(|&prefix| (0..radix).map(move |level| prefix && levels.contains(&level)))
+                .collect();
+        }
+        ControlledGate {
+            gate: Box::new(gate),
+            control_radixes,
+            control_dim,
+            active,
+        }
+    }
+
+    /// Find the parameters that maximize `|offset + Tr(env_matrix @ U)|`.
+    ///
+    /// Inactive control states add the trace of their diagonal blocks, so the
+    /// target gate is optimized against the sum of the active diagonal blocks
+    /// with the inactive traces added to `offset`. This is exact only when the
+    /// target gate's update can account for the offset; see `can_optimize_block`.
+    pub(crate) fn optimize_with_offset(
+        &self,
+        env_matrix: ArrayView2<c64>,
+        offset: c64,
+    ) -> Vec<f64> {
+        let n = env_matrix.nrows() / self.control_dim;
+        let mut sub_env = Array2::zeros((n, n));
+        let mut inactive = c64::new(0.0, 0.0);
+        for (c, &active) in self.active.iter().enumerate() {
+            let block = env_matrix.slice(s![c * n..(c + 1) * n, c * n..(c + 1) * n]);
+            if active {
+                sub_env += &block;
+            } else {
+                inactive += block.diag().sum();
+            }
+        }
+        optimize_block(&self.gate, sub_env.view(), offset + inactive)
+    }
+
+    /// Build a block-diagonal matrix with `block` on the active control states
+    /// and `inactive` everywhere else.
+    fn blocks(
+        &self,
+        mut base: ArrayViewMut2<c64>,
+        block: ArrayView2<c64>,
+        inactive: ArrayView2<c64>,
+    ) {
+        let n = block.nrows();
+        for (c, &active) in self.active.iter().enumerate() {
+            let mut diag = base.slice_mut(s![c * n..(c + 1) * n, c * n..(c + 1) * n]);
+            if active {
+                diag.assign(&block);
+            } else {
+                diag.assign(&inactive);
+            }
+        }
+    }
+}
+
+impl Unitary for ControlledGate {
+    fn num_params(&self) -> usize {
+        self.gate.num_params()
+    }
+
+    fn get_utry(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let utry = self.gate.get_utry(params, const_gates);
+        let n = utry.nrows();
+        let mut unitary = Array2::zeros((self.control_dim * n, self.control_dim * n));
+        self.blocks(unitary.view_mut(), utry.view(), Array2::eye(n).view());
+        unitary
+    }
+}
+
+impl Gradient for ControlledGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let (utry, grad) = self.gate.get_utry_and_grad(params, const_gates);
+        let n = utry.nrows();
+        let dim = self.control_dim * n;
+        let mut unitary = Array2::zeros((dim, dim));
+        self.blocks(unitary.view_mut(), utry.view(), Array2::eye(n).view());
+        let zeros = Array2::zeros((n, n));
+        let mut controlled_grad = Array3::zeros((self.num_params(), dim, dim));
+        for (i, d_utry) in grad.outer_iter().enumerate() {
+            self.blocks(
+                controlled_grad.slice_mut(s![i, .., ..]),
+                d_utry,
+                zeros.view(),
+            );
+        }
+        (unitary, controlled_grad)
+    }
+}
+
+impl Hessian for ControlledGate {}
+
+impl Size for ControlledGate {
+    fn num_qudits(&self) -> usize {
+        self.control_radixes.len() + self.gate.num_qudits()
+    }
+}
+
+impl Optimize for ControlledGate {
+    /// See `optimize_with_offset`.
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        self.optimize_with_offset(env_matrix.view(), c64::new(0.0, 0.0))
+    }
+
+    fn can_optimize(&self) -> bool {
+        can_optimize_block(&self.gate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+    use crate::ir::gates::{DiagonalGate, RXGate, SU4Gate, U2Gate, U3Gate};
+
+    #[test]
+    fn optimize_u3_accounts_for_inactive_blocks() {
+        let gate = ControlledGate::new(U3Gate::new().into(), 1, vec![3], vec![vec![1, 2]]);
+        assert_optimize_reaches_numeric_max(&gate.into(), 7);
+    }
+
+    #[test]
+    fn optimize_rotation_accounts_for_inactive_blocks() {
+        let gate = ControlledGate::new(RXGate::new().into(), 1, vec![2], vec![vec![1]]);
+        assert_optimize_reaches_numeric_max(&gate.into(), 8);
+    }
+
+    #[test]
+    fn optimize_diagonal_accounts_for_inactive_blocks() {
+        let diagonal = DiagonalGate::new(1, vec![2]);
+        let gate = ControlledGate::new(diagonal.into(), 1, vec![3], vec![vec![0, 2]]);
+        assert_optimize_reaches_numeric_max(&gate.into(), 9);
+    }
+
+    #[test]
+    fn phase_free_updates_cannot_optimize() {
+        let u2 = ControlledGate::new(U2Gate::new().into(), 1, vec![2], vec![vec![1]]);
+        assert!(!u2.can_optimize());
+        let su4 = ControlledGate::new(SU4Gate::new().into(), 1, vec![2], vec![vec![1]]);
+        assert!(!su4.can_optimize());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::ir::gates::{Gate, Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// The conjugate transpose of an arbitrary gate
+#[derive(Clone, Debug)]
+pub struct DaggerGate {
+    pub(super) gate: Box<Gate>,
+}
+
+impl DaggerGate {
+    pub fn new(gate: Gate) -> Self {
+        DaggerGate {
+            gate: Box::new(gate),
+        }
+    }
+}
+
+impl Unitary for DaggerGate {
+    fn num_params(&self) -> usize {
+        self.gate.num_params()
+    }
+
+    fn get_utry(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let utry = self.gate.get_utry(params, const_gates);
+        Array2::from_shape_fn(utry.raw_dim(), |(i, j)| utry[[j, i]].conj())
+    }
+}
+
+impl Gradient for DaggerGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        let grad = self.gate.get_grad(params, const_gates);
+        Array3::from_shape_fn(grad.raw_dim(), |(k, i, j)| grad[[k, j, i]].conj())
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let (utry, grad) = self.gate.get_utry_and_grad(params, const_gates);
+        (
+            Array2::from_shape_fn(utry.raw_dim(), |(i, j)| utry[[j, i]].conj()),
+            Array3::from_shape_fn(grad.raw_dim(), |(k, i, j)| grad[[k, j, i]].conj()),
+        )
+    }
+}
+
+impl Hessian for DaggerGate {
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        let hess = self.gate.get_hessian(params, const_gates);
+        Array4::from_shape_fn(hess.raw_dim(), |(k, l, i, j)| hess[[k, l, j, i]].conj())
+    }
+}
+
+impl Size for DaggerGate {
+    fn num_qudits(&self) -> usize {
+        self.gate.num_qudits()
+    }
+}
+
+impl Optimize for DaggerGate {
+    /// `Tr(E U^†)` is the conjugate of `Tr(E^† U)`, so the inner gate is
+    /// optimized against the conjugate transpose of the environment.
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let mut env =
+            Array2::from_shape_fn(env_matrix.raw_dim(), |(i, j)| env_matrix[[j, i]].conj());
+        self.gate.optimize(env.view_mut())
+    }
+
+    fn can_optimize(&self) -> bool {
+        self.gate.can_optimize()
+    }
+}
@@ -0,0 +1,157 @@
+use crate::ir::gates::{Gate, Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use super::{can_optimize_block, optimize_block};
+
+use ndarray::{s, Array2, Array3, ArrayView2, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// A gate embedded into a subset of the levels of higher-dimensional qudits
+#[derive(Clone, Debug)]
+pub struct EmbeddedGate {
+    gate: Box<Gate>,
+    radixes: Vec<usize>,
+    dim: usize,
+    indices: Vec<usize>,
+}
+
+impl EmbeddedGate {
+    /// Embed `gate` into qudits with the given `radixes`, where level `l` of
+    /// the gate's `q`-th qudit is mapped to level `level_maps[q][l]`.
+    pub fn new(gate: Gate, radixes: Vec<usize>, level_maps: Vec<Vec<usize>>) -> Self {
+        assert_eq!(gate.num_qudits(), radixes.len());
+        assert_eq!(level_maps.len(), radixes.len());
+        let dim = radixes.iter().product();
+        let mut indices = vec![0];
+        for (levels, &radix) in level_maps.iter().zip(&radixes) {
+            assert!(
+                levels.iter().all(|&level| level < radix),
+                "Level map {:?} is out of range for radix {}",
+                levels,
+                radix
+            );
+            indices = indices
+                .iter()
+                .flat_map(|index| levels.iter().map(move |level| index * radix + level))
+                .collect();
+        }
+        EmbeddedGate {
+            gate: Box::new(gate),
+            radixes,
+            dim,
+            indices,
+        }
+    }
+
+    /// Find the parameters that maximize `|offset + Tr(env_matrix @ U)|`.
+    ///
+    /// Levels outside the embedding add their diagonal of the environment to the
+    /// trace, so the inner gate is optimized against the environment restricted
+    /// to its levels with that added to `offset`. This is exact only when the
+    /// inner gate's update can account for the offset; see `can_optimize_block`.
+    pub(crate) fn optimize_with_offset(
+        &self,
+        env_matrix: ArrayView2<c64>,
+        offset: c64,
+    ) -> Vec<f64> {
+        let n = self.indices.len();
+        let sub_env = Array2::from_shape_fn((n, n), |(r, c)| {
+            env_matrix[[self.indices[r], self.indices[c]]]
+        });
+        let outside: c64 = (0..self.dim)
+            .filter(|k| !self.indices.contains(k))
+            .map(|k| env_matrix[[k, k]])
+            .sum();
+        optimize_block(&self.gate, sub_env.view(), offset + outside)
+    }
+
+    /// Place `matrix` at the embedded indices of `base`.
+    fn embed(&self, mut base: ArrayViewMut2<c64>, matrix: ArrayView2<c64>) {
+        assert_eq!(matrix.shape(), [self.indices.len(), self.indices.len()]);
+        for (r, &row) in self.indices.iter().enumerate() {
+            for (c, &col) in self.indices.iter().enumerate() {
+                base[[row, col]] = matrix[[r, c]];
+            }
+        }
+    }
+}
+
+impl Unitary for EmbeddedGate {
+    fn num_params(&self) -> usize {
+        self.gate.num_params()
+    }
+
+    fn get_utry(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let mut unitary = Array2::eye(self.dim);
+        self.embed(
+            unitary.view_mut(),
+            self.gate.get_utry(params, const_gates).view(),
+        );
+        unitary
+    }
+}
+
+impl Gradient for EmbeddedGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let (utry, grad) = self.gate.get_utry_and_grad(params, const_gates);
+        let mut unitary = Array2::eye(self.dim);
+        self.embed(unitary.view_mut(), utry.view());
+        let mut embedded_grad = Array3::zeros((self.num_params(), self.dim, self.dim));
+        for (i, d_utry) in grad.outer_iter().enumerate() {
+            self.embed(embedded_grad.slice_mut(s![i, .., ..]), d_utry);
+        }
+        (unitary, embedded_grad)
+    }
+}
+
+impl Hessian for EmbeddedGate {}
+
+impl Size for EmbeddedGate {
+    fn num_qudits(&self) -> usize {
+        self.radixes.len()
+    }
+}
+
+impl Optimize for EmbeddedGate {
+    /// See `optimize_with_offset`.
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        self.optimize_with_offset(env_matrix.view(), c64::new(0.0, 0.0))
+    }
+
+    fn can_optimize(&self) -> bool {
+        can_optimize_block(&self.gate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+    use crate::ir::gates::{RXGate, U2Gate, U3Gate};
+
+    #[test]
+    fn optimize_u3_accounts_for_other_levels() {
+        let gate = EmbeddedGate::new(U3Gate::new().into(), vec![3], vec![vec![0, 2]]);
+        assert_optimize_reaches_numeric_max(&gate.into(), 5);
+    }
+
+    #[test]
+    fn optimize_rotation_accounts_for_other_levels() {
+        let gate = EmbeddedGate::new(RXGate::new().into(), vec![3], vec![vec![1, 2]]);
+        assert_optimize_reaches_numeric_max(&gate.into(), 6);
+    }
+
+    #[test]
+    fn phase_free_update_cannot_optimize() {
+        let gate = EmbeddedGate::new(U2Gate::new().into(), vec![3], vec![vec![0, 2]]);
+        assert!(!gate.can_optimize());
+    }
+}
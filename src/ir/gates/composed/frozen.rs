@@ -0,0 +1,181 @@
+use crate::ir::gates::{Gate, Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2, Axis};
+use ndarray_linalg::c64;
+use ndarray_linalg::trace::Trace;
+
+/// An arbitrary gate with a subset of its parameters fixed
+#[derive(Clone, Debug)]
+pub struct FrozenParameterGate {
+    gate: Box<Gate>,
+    frozen_params: Vec<(usize, f64)>,
+    free_params: Vec<usize>,
+}
+
+impl FrozenParameterGate {
+    /// Fix each `(index, value)` pair in `frozen_params` on the inner gate;
+    /// the remaining parameters keep their relative order.
+    pub fn new(gate: Gate, mut frozen_params: Vec<(usize, f64)>) -> Self {
+        frozen_params.sort_by_key(|&(index, _)| index);
+        frozen_params.dedup_by_key(|&mut (index, _)| index);
+        assert!(
+            frozen_params
+                .iter()
+                .all(|&(index, _)| index < gate.num_params()),
+            "Frozen parameter index out of range for gate with {} parameters",
+            gate.num_params()
+        );
+        let free_params = (0..gate.num_params())
+            .filter(|i| frozen_params.iter().all(|&(index, _)| index != *i))
+            .collect();
+        FrozenParameterGate {
+            gate: Box::new(gate),
+            frozen_params,
+            free_params,
+        }
+    }
+
+    /// Expand the free parameters into a full parameter vector for the inner gate.
+    fn full_params(&self, params: &[f64]) -> Vec<f64> {
+        assert_eq!(params.len(), self.free_params.len());
+        let mut full = vec![0.0; self.gate.num_params()];
+        for (&index, &value) in self.free_params.iter().zip(params) {
+            full[index] = value;
+        }
+        for &(index, value) in &self.frozen_params {
+            full[index] = value;
+        }
+        full
+    }
+
+    /// Calculate `|Tr(env_matrix @ U)|` and its gradient with respect to the free parameters.
+    ///
+    /// `optimize` is not given the constant gates, so none are passed on. Only
+    /// inner gates with an update get here (see `can_optimize`), and none of
+    /// those read the constant gates.
+    fn trace_and_grad(&self, env_matrix: &Array2<c64>, params: &[f64]) -> (f64, Vec<f64>) {
+        let (utry, grad) = self.get_utry_and_grad(params, &[]);
+        let tr = env_matrix.dot(&utry).trace().unwrap();
+        let norm = tr.norm();
+        if norm == 0.0 {
+            // |Tr| has no gradient at zero, so the ascent stops here.
+            return (norm, vec![0.0; params.len()]);
+        }
+        let d_norm = grad
+            .outer_iter()
+            .map(|d_utry| (tr.conj() * env_matrix.dot(&d_utry).trace().unwrap()).re / norm)
+            .collect();
+        (norm, d_norm)
+    }
+}
+
+impl Unitary for FrozenParameterGate {
+    fn num_params(&self) -> usize {
+        self.free_params.len()
+    }
+
+    fn get_utry(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        self.gate.get_utry(&self.full_params(params), const_gates)
+    }
+}
+
+impl Gradient for FrozenParameterGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let (utry, grad) = self
+            .gate
+            .get_utry_and_grad(&self.full_params(params), const_gates);
+        (utry, grad.select(Axis(0), &self.free_params))
+    }
+}
+
+impl Hessian for FrozenParameterGate {
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        self.gate
+            .get_hessian(&self.full_params(params), const_gates)
+            .select(Axis(0), &self.free_params)
+            .select(Axis(1), &self.free_params)
+    }
+}
+
+impl Size for FrozenParameterGate {
+    fn num_qudits(&self) -> usize {
+        self.gate.num_qudits()
+    }
+}
+
+impl Optimize for FrozenParameterGate {
+    /// Maximize `|Tr(env_matrix @ U)|` over the free parameters.
+    ///
+    /// With nothing frozen this is the inner gate's update. Otherwise the inner
+    /// update, which also moves the frozen parameters, only gives a starting
+    /// point. Its free components are refined by gradient ascent on
+    /// `|Tr(env_matrix @ U)|` with the frozen values in place, doubling the step
+    /// after each accepted move and halving it until the Armijo condition holds,
+    /// for at most 100 steps. The result is a local maximum, not necessarily the
+    /// global one.
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        if self.frozen_params.is_empty() {
+            return self.gate.optimize(env_matrix);
+        }
+        let env = env_matrix.to_owned();
+        let start = self.gate.optimize(env_matrix);
+        let mut params: Vec<f64> = self.free_params.iter().map(|&i| start[i]).collect();
+        if params.is_empty() {
+            return params;
+        }
+        let (mut value, mut grad) = self.trace_and_grad(&env, &params);
+        let mut step = 1.0;
+        for _ in 0..100 {
+            let grad_norm_sq: f64 = grad.iter().map(|g| g * g).sum();
+            if grad_norm_sq < 1e-20 {
+                break;
+            }
+            loop {
+                let candidate: Vec<f64> = params
+                    .iter()
+                    .zip(&grad)
+                    .map(|(p, g)| p + step * g)
+                    .collect();
+                let (new_value, new_grad) = self.trace_and_grad(&env, &candidate);
+                if new_value >= value + 0.5 * step * grad_norm_sq {
+                    params = candidate;
+                    value = new_value;
+                    grad = new_grad;
+                    step *= 2.0;
+                    break;
+                }
+                step *= 0.5;
+                if step < 1e-12 {
+                    return params;
+                }
+            }
+        }
+        params
+    }
+
+    fn can_optimize(&self) -> bool {
+        self.gate.can_optimize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+    use crate::ir::gates::U3Gate;
+
+    #[test]
+    fn optimize_reaches_numeric_maximum() {
+        let gate = FrozenParameterGate::new(U3Gate::new().into(), vec![(2, 0.7)]);
+        assert_optimize_reaches_numeric_max(&gate.into(), 7);
+    }
+}
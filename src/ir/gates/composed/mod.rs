@@ -0,0 +1,92 @@
+mod circuit;
+mod controlled;
+mod dagger;
+mod embedded;
+mod frozen;
+
+pub use circuit::CircuitGate;
+pub use controlled::ControlledGate;
+pub use dagger::DaggerGate;
+pub use embedded::EmbeddedGate;
+pub use frozen::FrozenParameterGate;
+
+use std::f64::consts::PI;
+
+use crate::i;
+use crate::ir::gates::{Gate, Optimize, Unitary};
+
+use ndarray::ArrayView2;
+use ndarray_linalg::c64;
+
+/// Whether `optimize_block` finds the exact optimum of `gate`.
+///
+/// Updates that strip the global phase (U2, U8, SU4, `SpecialUnitaryGate`,
+/// PhasedXZ) return the same parameters however the environment is rotated, so
+/// they cannot account for the trace of the blocks a wrapper does not touch.
+pub(crate) fn can_optimize_block(gate: &Gate) -> bool {
+    match gate {
+        Gate::U2(_) | Gate::U8(_) | Gate::SU4(_) | Gate::SpecialUnitary(_) | Gate::PhasedXZ(_) => {
+            false
+        }
+        Gate::Dagger(d) => can_optimize_block(&d.gate),
+        _ => gate.can_optimize(),
+    }
+}
+
+/// Find the parameters of `gate` that maximize `|offset + Tr(env_matrix @ U)|`,
+/// where `offset` is the trace of the blocks of a wrapper that `gate` does not
+/// touch.
+///
+/// U3, CU3 and the nested wrappers account for the offset directly. Any other
+/// gate must have an update that maximizes `Re Tr(env_matrix @ U)`, such as RX,
+/// `DiagonalGate` or `VariableUnitaryGate`. Then the best value for a
+/// rotated environment `e^(-ia) env_matrix` is
+/// `f(a) = max_U Re(e^(-ia) (offset + Tr(env_matrix @ U)))`, and the maximum of
+/// `f` over `a` is the maximum of `|offset + Tr(env_matrix @ U)|`. A coarse scan
+/// brackets it and a golden-section search refines it.
+pub(crate) fn optimize_block(gate: &Gate, env_matrix: ArrayView2<c64>, offset: c64) -> Vec<f64> {
+    match gate {
+        Gate::U3(u) => return u.optimize_with_offset(env_matrix, offset),
+        Gate::CU3(c) => return c.optimize_with_offset(env_matrix, offset),
+        Gate::Embedded(e) => return e.optimize_with_offset(env_matrix, offset),
+        Gate::Controlled(c) => return c.optimize_with_offset(env_matrix, offset),
+        _ => (),
+    }
+    if gate.num_params() == 0 {
+        return vec![];
+    }
+
+    let solve = |phase: f64| {
+        let rotation = i!(-phase).exp();
+        let mut rotated = env_matrix.mapv(|x| x * rotation);
+        let params = gate.optimize(rotated.view_mut());
+        let trace = offset + env_matrix.dot(&gate.get_utry(&params, &[])).diag().sum();
+        ((rotation * trace).re, params)
+    };
+
+    let steps = 32;
+    let width = 2.0 * PI / steps as f64;
+    let start = (0..steps)
+        .map(|k| k as f64 * width)
+        .fold((0.0, f64::NEG_INFINITY), |best, phase| {
+            let value = solve(phase).0;
+            if value > best.1 {
+                (phase, value)
+            } else {
+                best
+            }
+        })
+        .0;
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut lo, mut hi) = (start - width, start + width);
+    while hi - lo > 1e-12 {
+        let left = hi - ratio * (hi - lo);
+        let right = lo + ratio * (hi - lo);
+        if solve(left).0 < solve(right).0 {
+            lo = left;
+        } else {
+            hi = right;
+        }
+    }
+    solve((lo + hi) / 2.0).1
+}
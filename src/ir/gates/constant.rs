@@ -3,6 +3,7 @@ use ndarray::Array3;
 use ndarray_linalg::c64;
 
 use super::Gradient;
+use super::Hessian;
 use super::Optimize;
 use super::Size;
 use super::Unitary;
@@ -36,8 +37,9 @@ impl Unitary for ConstantGate {
 }
 
 impl Gradient for ConstantGate {
-    fn get_grad(&self, _params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
-        Array3::zeros((0, 0, 0))
+    fn get_grad(&self, _params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        let dim = const_gates[self.index].nrows();
+        Array3::zeros((0, dim, dim))
     }
 
     fn get_utry_and_grad(
@@ -45,8 +47,16 @@ impl Gradient for ConstantGate {
         _params: &[f64],
         const_gates: &[Array2<c64>],
     ) -> (Array2<c64>, Array3<c64>) {
-        (const_gates[self.index].clone(), Array3::zeros((0, 0, 0)))
+        let utry = const_gates[self.index].clone();
+        let dim = utry.nrows();
+        (utry, Array3::zeros((0, dim, dim)))
     }
 }
 
-impl Optimize for ConstantGate {}
+impl Hessian for ConstantGate {}
+
+impl Optimize for ConstantGate {
+    fn can_optimize(&self) -> bool {
+        false
+    }
+}
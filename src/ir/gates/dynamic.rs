@@ -1,11 +1,11 @@
 use std::fmt;
 
-use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2};
 use ndarray_linalg::c64;
 
-use super::{Gradient, Optimize, Size, Unitary};
+use super::{FallbackGradient, Gradient, Hessian, Optimize, Size, Unitary};
 
-pub trait DynGate: Unitary + Gradient + Size + Optimize + fmt::Debug {}
+pub trait DynGate: Unitary + Gradient + Hessian + Size + Optimize + fmt::Debug {}
 
 impl<T> DynGate for Box<T> where T: DynGate {}
 
@@ -37,6 +37,27 @@ where
     ) -> (Array2<c64>, Array3<c64>) {
         (**self).get_utry_and_grad(params, const_gates)
     }
+
+    fn fallback_gradient(&self) -> FallbackGradient {
+        (**self).fallback_gradient()
+    }
+}
+
+impl<T> Hessian for Box<T>
+where
+    T: DynGate,
+{
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        (**self).get_hessian(params, const_gates)
+    }
+
+    fn get_utry_grad_and_hessian(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>, Array4<c64>) {
+        (**self).get_utry_grad_and_hessian(params, const_gates)
+    }
 }
 
 impl<T> Size for Box<T>
@@ -55,4 +76,8 @@ where
     fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
         (**self).optimize(env_matrix)
     }
+
+    fn can_optimize(&self) -> bool {
+        (**self).can_optimize()
+    }
 }
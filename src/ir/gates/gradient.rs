@@ -1,9 +1,26 @@
+use std::f64::consts::PI;
+
 use enum_dispatch::enum_dispatch;
-use ndarray::{Array2, Array3};
+use ndarray::{Array2, Array3, Axis};
 use ndarray_linalg::c64;
 
 use super::Unitary;
+
+/// How to differentiate a gate that has no analytic gradient.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum FallbackGradient {
+    /// Central finite differences, refined by Richardson extrapolation.
+    #[default]
+    FiniteDifference,
+    /// The two-term parameter-shift rule. This is exact when every parameter
+    /// enters as `exp(-iθG)` for a generator `G` with eigenvalues `±1/2`.
+    ParameterShift,
+}
+
 /// Gradient should be implemented for all gates where one can take their gradient.
+///
+/// Gates without an analytic derivative can rely on the default methods, which
+/// differentiate `get_utry` numerically as described by `fallback_gradient`.
 #[enum_dispatch]
 pub trait Gradient: Unitary {
     /// Get the gradient and unitary together
@@ -11,8 +28,102 @@ pub trait Gradient: Unitary {
         &self,
         params: &[f64],
         const_gates: &[Array2<c64>],
-    ) -> (Array2<c64>, Array3<c64>);
+    ) -> (Array2<c64>, Array3<c64>) {
+        (
+            self.get_utry(params, const_gates),
+            self.get_grad(params, const_gates),
+        )
+    }
 
     /// Get the gradient of `self`.
-    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64>;
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        match self.fallback_gradient() {
+            FallbackGradient::FiniteDifference => finite_difference_grad(self, params, const_gates),
+            FallbackGradient::ParameterShift => parameter_shift_grad(self, params, const_gates),
+        }
+    }
+
+    /// The numerical method used by the default `get_grad`.
+    fn fallback_gradient(&self) -> FallbackGradient {
+        FallbackGradient::FiniteDifference
+    }
+}
+
+/// Differentiate `get_utry` with central finite differences.
+///
+/// Differences at steps `h` and `h / 2` are combined by Richardson extrapolation,
+/// cancelling the `O(h²)` error term. Each step is rounded so that `θ ± h` is
+/// exactly representable.
+pub fn finite_difference_grad<G: Unitary + ?Sized>(
+    gate: &G,
+    params: &[f64],
+    const_gates: &[Array2<c64>],
+) -> Array3<c64> {
+    let central_difference = |k: usize, step: f64| {
+        let mut shifted = params.to_vec();
+        let step = (params[k] + step) - params[k];
+        shifted[k] = params[k] + step;
+        let forward = gate.get_utry(&shifted, const_gates);
+        shifted[k] = params[k] - step;
+        let backward = gate.get_utry(&shifted, const_gates);
+        (forward - backward) / c64::new(2.0 * step, 0.0)
+    };
+
+    let utry = gate.get_utry(params, const_gates);
+    let mut grad = Array3::zeros((params.len(), utry.nrows(), utry.ncols()));
+    let step = f64::EPSILON.powf(0.2);
+    for (k, mut d_utry) in grad.axis_iter_mut(Axis(0)).enumerate() {
+        let coarse = central_difference(k, step);
+        let fine = central_difference(k, step / 2.0);
+        d_utry.assign(&((fine * c64::new(4.0, 0.0) - coarse) / c64::new(3.0, 0.0)));
+    }
+    grad
+}
+
+/// Differentiate `get_utry` with the parameter-shift rule
+/// `dU/dθ = (U(θ + π) - U(θ - π)) / 4`.
+pub fn parameter_shift_grad<G: Unitary + ?Sized>(
+    gate: &G,
+    params: &[f64],
+    const_gates: &[Array2<c64>],
+) -> Array3<c64> {
+    let utry = gate.get_utry(params, const_gates);
+    let mut grad = Array3::zeros((params.len(), utry.nrows(), utry.ncols()));
+    let mut shifted = params.to_vec();
+    for (k, mut d_utry) in grad.axis_iter_mut(Axis(0)).enumerate() {
+        shifted[k] = params[k] + PI;
+        let forward = gate.get_utry(&shifted, const_gates);
+        shifted[k] = params[k] - PI;
+        let backward = gate.get_utry(&shifted, const_gates);
+        shifted[k] = params[k];
+        d_utry.assign(&((forward - backward) / c64::new(4.0, 0.0)));
+    }
+    grad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+    use crate::ir::gates::{Gate, RXGate, RXXGate, RYGate, RYYGate, RZGate, RZZGate};
+
+    /// Each of these gates is `exp(-iθG)` with `G` having eigenvalues `±1/2`.
+    #[test]
+    fn parameter_shift_matches_analytic_gradient() {
+        let gates: Vec<Gate> = vec![
+            RXGate::new().into(),
+            RYGate::new().into(),
+            RZGate::new().into(),
+            RXXGate::new().into(),
+            RYYGate::new().into(),
+            RZZGate::new().into(),
+        ];
+        let mut rng = rng(18);
+        for gate in &gates {
+            let params = random_params(gate.num_params(), &mut rng);
+            let shifted = parameter_shift_grad(gate, &params, &[]);
+            let analytic = gate.get_grad(&params, &[]);
+            assert!((shifted - analytic).iter().all(|x| x.norm() < 1e-12));
+        }
+    }
 }
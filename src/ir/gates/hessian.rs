@@ -0,0 +1,58 @@
+use ndarray::{Array2, Array3, Array4, Axis};
+use ndarray_linalg::c64;
+
+use super::Gradient;
+
+/// Hessian should be implemented for gates whose second derivatives are needed,
+/// such as by Newton-type optimizers.
+///
+/// The Hessian has shape `(num_params, num_params, dim, dim)`. Gates without an
+/// analytic form can rely on the default methods, which differentiate `get_grad`.
+pub trait Hessian: Gradient {
+    /// Get the unitary, gradient and Hessian together
+    fn get_utry_grad_and_hessian(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>, Array4<c64>) {
+        let (utry, grad) = self.get_utry_and_grad(params, const_gates);
+        (utry, grad, self.get_hessian(params, const_gates))
+    }
+
+    /// Get the Hessian of `self`.
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        finite_difference_hessian(self, params, const_gates)
+    }
+}
+
+/// Differentiate `get_grad` with central finite differences.
+///
+/// Differences at steps `h` and `h / 2` are combined by Richardson extrapolation,
+/// and the result is symmetrized over the two parameter axes.
+pub fn finite_difference_hessian<G: Gradient + ?Sized>(
+    gate: &G,
+    params: &[f64],
+    const_gates: &[Array2<c64>],
+) -> Array4<c64> {
+    let central_difference = |k: usize, step: f64| {
+        let mut shifted = params.to_vec();
+        let step = (params[k] + step) - params[k];
+        shifted[k] = params[k] + step;
+        let forward = gate.get_grad(&shifted, const_gates);
+        shifted[k] = params[k] - step;
+        let backward = gate.get_grad(&shifted, const_gates);
+        (forward - backward) / c64::new(2.0 * step, 0.0)
+    };
+
+    let grad = gate.get_grad(params, const_gates);
+    let (num_params, rows, cols) = grad.dim();
+    let mut hess = Array4::zeros((num_params, num_params, rows, cols));
+    let step = f64::EPSILON.powf(0.2);
+    for (k, mut d_grad) in hess.axis_iter_mut(Axis(0)).enumerate() {
+        let coarse = central_difference(k, step);
+        let fine = central_difference(k, step / 2.0);
+        d_grad.assign(&((fine * c64::new(4.0, 0.0) - coarse) / c64::new(3.0, 0.0)));
+    }
+    let transposed = hess.view().permuted_axes([1, 0, 2, 3]).to_owned();
+    (hess + transposed) / c64::new(2.0, 0.0)
+}
@@ -1,23 +1,37 @@
+mod check;
+mod composed;
 mod constant;
 mod dynamic;
 mod gradient;
+mod hessian;
 mod optimize;
 mod parameterized;
+mod permutation;
+mod qudit;
 mod size;
+#[cfg(test)]
+pub(crate) mod test_utils;
 mod unitary;
 mod utils;
 
 use std::sync::Arc;
 
+pub use self::check::{check_gradient, unitarity_error, GradientCheck};
+pub use self::composed::*;
 pub use self::constant::ConstantGate;
 pub use self::dynamic::DynGate;
-pub use self::gradient::Gradient;
+pub use self::gradient::{
+    finite_difference_grad, parameter_shift_grad, FallbackGradient, Gradient,
+};
+pub use self::hessian::{finite_difference_hessian, Hessian};
 pub use self::optimize::Optimize;
 pub use self::parameterized::*;
+pub use self::permutation::PermutationGate;
+pub use self::qudit::*;
 pub use self::size::Size;
 pub use self::unitary::Unitary;
 
-use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2};
 use ndarray_linalg::c64;
 
 use derive_more::From;
@@ -25,6 +39,7 @@ use derive_more::From;
 #[derive(Clone, Debug, From)]
 pub enum Gate {
     Constant(ConstantGate),
+    Permutation(PermutationGate),
     U1(U1Gate),
     U2(U2Gate),
     U3(U3Gate),
@@ -38,8 +53,27 @@ pub enum Gate {
     CRX(CRXGate),
     CRY(CRYGate),
     CRZ(CRZGate),
+    SU4(SU4Gate),
+    CSUM(CSUMGate),
+    CZ(CZGate),
+    CPhase(CPhaseGate),
+    CU3(CU3Gate),
+    FSim(FSimGate),
+    PhasedXZ(PhasedXZGate),
+    XXPlusYY(XXPlusYYGate),
+    RXSubGate(RXSubGate),
+    RYSubGate(RYSubGate),
     RZSubGate(RZSubGate),
+    SpecialUnitary(SpecialUnitaryGate),
+    PauliRotation(PauliRotationGate),
     VariableUnitary(VariableUnitaryGate),
+    Diagonal(DiagonalGate),
+    Hamiltonian(HamiltonianGate),
+    Embedded(EmbeddedGate),
+    Controlled(ControlledGate),
+    Dagger(DaggerGate),
+    Frozen(FrozenParameterGate),
+    Circuit(CircuitGate),
     Dynamic(Arc<dyn DynGate + Send + Sync>),
 }
 
@@ -47,6 +81,7 @@ impl Unitary for Gate {
     fn num_params(&self) -> usize {
         match self {
             Gate::Constant(_) => 0,
+            Gate::Permutation(_) => 0,
             Gate::U1(_) => 1,
             Gate::U2(_) => 2,
             Gate::U3(_) => 3,
@@ -60,8 +95,27 @@ impl Unitary for Gate {
             Gate::CRX(_) => 1,
             Gate::CRY(_) => 1,
             Gate::CRZ(_) => 1,
+            Gate::SU4(_) => 15,
+            Gate::CSUM(_) => 0,
+            Gate::CZ(_) => 0,
+            Gate::CPhase(_) => 1,
+            Gate::CU3(_) => 3,
+            Gate::FSim(_) => 2,
+            Gate::PhasedXZ(_) => 3,
+            Gate::XXPlusYY(_) => 2,
+            Gate::RXSubGate(_) => 1,
+            Gate::RYSubGate(_) => 1,
             Gate::RZSubGate(_) => 1,
+            Gate::SpecialUnitary(s) => s.num_params(),
+            Gate::PauliRotation(_) => 1,
             Gate::VariableUnitary(v) => v.num_params(),
+            Gate::Diagonal(d) => d.num_params(),
+            Gate::Hamiltonian(h) => h.num_params(),
+            Gate::Embedded(e) => e.num_params(),
+            Gate::Controlled(c) => c.num_params(),
+            Gate::Dagger(d) => d.num_params(),
+            Gate::Frozen(f) => f.num_params(),
+            Gate::Circuit(c) => c.num_params(),
             Gate::Dynamic(d) => d.num_params(),
         }
     }
@@ -69,6 +123,7 @@ impl Unitary for Gate {
     fn get_utry(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
         match self {
             Gate::Constant(c) => c.get_utry(params, const_gates),
+            Gate::Permutation(p) => p.get_utry(params, const_gates),
             Gate::U1(u) => u.get_utry(params, const_gates),
             Gate::U2(u) => u.get_utry(params, const_gates),
             Gate::U3(u) => u.get_utry(params, const_gates),
@@ -82,8 +137,27 @@ impl Unitary for Gate {
             Gate::CRX(x) => x.get_utry(params, const_gates),
             Gate::CRY(y) => y.get_utry(params, const_gates),
             Gate::CRZ(z) => z.get_utry(params, const_gates),
+            Gate::SU4(u) => u.get_utry(params, const_gates),
+            Gate::CSUM(c) => c.get_utry(params, const_gates),
+            Gate::CZ(c) => c.get_utry(params, const_gates),
+            Gate::CPhase(c) => c.get_utry(params, const_gates),
+            Gate::CU3(c) => c.get_utry(params, const_gates),
+            Gate::FSim(f) => f.get_utry(params, const_gates),
+            Gate::PhasedXZ(p) => p.get_utry(params, const_gates),
+            Gate::XXPlusYY(x) => x.get_utry(params, const_gates),
+            Gate::RXSubGate(x) => x.get_utry(params, const_gates),
+            Gate::RYSubGate(y) => y.get_utry(params, const_gates),
             Gate::RZSubGate(z) => z.get_utry(params, const_gates),
+            Gate::SpecialUnitary(s) => s.get_utry(params, const_gates),
+            Gate::PauliRotation(p) => p.get_utry(params, const_gates),
             Gate::VariableUnitary(v) => v.get_utry(params, const_gates),
+            Gate::Diagonal(d) => d.get_utry(params, const_gates),
+            Gate::Hamiltonian(h) => h.get_utry(params, const_gates),
+            Gate::Embedded(e) => e.get_utry(params, const_gates),
+            Gate::Controlled(c) => c.get_utry(params, const_gates),
+            Gate::Dagger(d) => d.get_utry(params, const_gates),
+            Gate::Frozen(f) => f.get_utry(params, const_gates),
+            Gate::Circuit(c) => c.get_utry(params, const_gates),
             Gate::Dynamic(d) => d.get_utry(params, const_gates),
         }
     }
@@ -93,6 +167,7 @@ impl Gradient for Gate {
     fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
         match self {
             Gate::Constant(c) => c.get_grad(params, const_gates),
+            Gate::Permutation(p) => p.get_grad(params, const_gates),
             Gate::U1(u) => u.get_grad(params, const_gates),
             Gate::U2(u) => u.get_grad(params, const_gates),
             Gate::U3(u) => u.get_grad(params, const_gates),
@@ -106,8 +181,27 @@ impl Gradient for Gate {
             Gate::CRX(x) => x.get_grad(params, const_gates),
             Gate::CRY(y) => y.get_grad(params, const_gates),
             Gate::CRZ(z) => z.get_grad(params, const_gates),
+            Gate::SU4(u) => u.get_grad(params, const_gates),
+            Gate::CSUM(c) => c.get_grad(params, const_gates),
+            Gate::CZ(c) => c.get_grad(params, const_gates),
+            Gate::CPhase(c) => c.get_grad(params, const_gates),
+            Gate::CU3(c) => c.get_grad(params, const_gates),
+            Gate::FSim(f) => f.get_grad(params, const_gates),
+            Gate::PhasedXZ(p) => p.get_grad(params, const_gates),
+            Gate::XXPlusYY(x) => x.get_grad(params, const_gates),
+            Gate::RXSubGate(x) => x.get_grad(params, const_gates),
+            Gate::RYSubGate(y) => y.get_grad(params, const_gates),
             Gate::RZSubGate(z) => z.get_grad(params, const_gates),
+            Gate::SpecialUnitary(s) => s.get_grad(params, const_gates),
+            Gate::PauliRotation(p) => p.get_grad(params, const_gates),
             Gate::VariableUnitary(v) => v.get_grad(params, const_gates),
+            Gate::Diagonal(d) => d.get_grad(params, const_gates),
+            Gate::Hamiltonian(h) => h.get_grad(params, const_gates),
+            Gate::Embedded(e) => e.get_grad(params, const_gates),
+            Gate::Controlled(c) => c.get_grad(params, const_gates),
+            Gate::Dagger(d) => d.get_grad(params, const_gates),
+            Gate::Frozen(f) => f.get_grad(params, const_gates),
+            Gate::Circuit(c) => c.get_grad(params, const_gates),
             Gate::Dynamic(d) => d.get_grad(params, const_gates),
         }
     }
@@ -119,6 +213,7 @@ impl Gradient for Gate {
     ) -> (Array2<c64>, Array3<c64>) {
         match self {
             Gate::Constant(c) => c.get_utry_and_grad(params, const_gates),
+            Gate::Permutation(p) => p.get_utry_and_grad(params, const_gates),
             Gate::U1(u) => u.get_utry_and_grad(params, const_gates),
             Gate::U2(u) => u.get_utry_and_grad(params, const_gates),
             Gate::U3(u) => u.get_utry_and_grad(params, const_gates),
@@ -132,17 +227,127 @@ impl Gradient for Gate {
             Gate::CRX(x) => x.get_utry_and_grad(params, const_gates),
             Gate::CRY(y) => y.get_utry_and_grad(params, const_gates),
             Gate::CRZ(z) => z.get_utry_and_grad(params, const_gates),
+            Gate::SU4(u) => u.get_utry_and_grad(params, const_gates),
+            Gate::CSUM(c) => c.get_utry_and_grad(params, const_gates),
+            Gate::CZ(c) => c.get_utry_and_grad(params, const_gates),
+            Gate::CPhase(c) => c.get_utry_and_grad(params, const_gates),
+            Gate::CU3(c) => c.get_utry_and_grad(params, const_gates),
+            Gate::FSim(f) => f.get_utry_and_grad(params, const_gates),
+            Gate::PhasedXZ(p) => p.get_utry_and_grad(params, const_gates),
+            Gate::XXPlusYY(x) => x.get_utry_and_grad(params, const_gates),
+            Gate::RXSubGate(x) => x.get_utry_and_grad(params, const_gates),
+            Gate::RYSubGate(y) => y.get_utry_and_grad(params, const_gates),
             Gate::RZSubGate(z) => z.get_utry_and_grad(params, const_gates),
+            Gate::SpecialUnitary(s) => s.get_utry_and_grad(params, const_gates),
+            Gate::PauliRotation(p) => p.get_utry_and_grad(params, const_gates),
             Gate::VariableUnitary(v) => v.get_utry_and_grad(params, const_gates),
+            Gate::Diagonal(d) => d.get_utry_and_grad(params, const_gates),
+            Gate::Hamiltonian(h) => h.get_utry_and_grad(params, const_gates),
+            Gate::Embedded(e) => e.get_utry_and_grad(params, const_gates),
+            Gate::Controlled(c) => c.get_utry_and_grad(params, const_gates),
+            Gate::Dagger(d) => d.get_utry_and_grad(params, const_gates),
+            Gate::Frozen(f) => f.get_utry_and_grad(params, const_gates),
+            Gate::Circuit(c) => c.get_utry_and_grad(params, const_gates),
             Gate::Dynamic(d) => d.get_utry_and_grad(params, const_gates),
         }
     }
 }
 
+impl Hessian for Gate {
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        match self {
+            Gate::Constant(c) => c.get_hessian(params, const_gates),
+            Gate::Permutation(p) => p.get_hessian(params, const_gates),
+            Gate::U1(u) => u.get_hessian(params, const_gates),
+            Gate::U2(u) => u.get_hessian(params, const_gates),
+            Gate::U3(u) => u.get_hessian(params, const_gates),
+            Gate::U8(u) => u.get_hessian(params, const_gates),
+            Gate::RX(x) => x.get_hessian(params, const_gates),
+            Gate::RY(y) => y.get_hessian(params, const_gates),
+            Gate::RZ(z) => z.get_hessian(params, const_gates),
+            Gate::RXX(x) => x.get_hessian(params, const_gates),
+            Gate::RYY(y) => y.get_hessian(params, const_gates),
+            Gate::RZZ(z) => z.get_hessian(params, const_gates),
+            Gate::CRX(x) => x.get_hessian(params, const_gates),
+            Gate::CRY(y) => y.get_hessian(params, const_gates),
+            Gate::CRZ(z) => z.get_hessian(params, const_gates),
+            Gate::SU4(u) => u.get_hessian(params, const_gates),
+            Gate::CSUM(c) => c.get_hessian(params, const_gates),
+            Gate::CZ(c) => c.get_hessian(params, const_gates),
+            Gate::CPhase(c) => c.get_hessian(params, const_gates),
+            Gate::CU3(c) => c.get_hessian(params, const_gates),
+            Gate::FSim(f) => f.get_hessian(params, const_gates),
+            Gate::PhasedXZ(p) => p.get_hessian(params, const_gates),
+            Gate::XXPlusYY(x) => x.get_hessian(params, const_gates),
+            Gate::RXSubGate(x) => x.get_hessian(params, const_gates),
+            Gate::RYSubGate(y) => y.get_hessian(params, const_gates),
+            Gate::RZSubGate(z) => z.get_hessian(params, const_gates),
+            Gate::SpecialUnitary(s) => s.get_hessian(params, const_gates),
+            Gate::PauliRotation(p) => p.get_hessian(params, const_gates),
+            Gate::VariableUnitary(v) => v.get_hessian(params, const_gates),
+            Gate::Diagonal(d) => d.get_hessian(params, const_gates),
+            Gate::Hamiltonian(h) => h.get_hessian(params, const_gates),
+            Gate::Embedded(e) => e.get_hessian(params, const_gates),
+            Gate::Controlled(c) => c.get_hessian(params, const_gates),
+            Gate::Dagger(d) => d.get_hessian(params, const_gates),
+            Gate::Frozen(f) => f.get_hessian(params, const_gates),
+            Gate::Circuit(c) => c.get_hessian(params, const_gates),
+            Gate::Dynamic(d) => d.get_hessian(params, const_gates),
+        }
+    }
+
+    fn get_utry_grad_and_hessian(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>, Array4<c64>) {
+        match self {
+            Gate::Constant(c) => c.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Permutation(p) => p.get_utry_grad_and_hessian(params, const_gates),
+            Gate::U1(u) => u.get_utry_grad_and_hessian(params, const_gates),
+            Gate::U2(u) => u.get_utry_grad_and_hessian(params, const_gates),
+            Gate::U3(u) => u.get_utry_grad_and_hessian(params, const_gates),
+            Gate::U8(u) => u.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RX(x) => x.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RY(y) => y.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RZ(z) => z.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RXX(x) => x.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RYY(y) => y.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RZZ(z) => z.get_utry_grad_and_hessian(params, const_gates),
+            Gate::CRX(x) => x.get_utry_grad_and_hessian(params, const_gates),
+            Gate::CRY(y) => y.get_utry_grad_and_hessian(params, const_gates),
+            Gate::CRZ(z) => z.get_utry_grad_and_hessian(params, const_gates),
+            Gate::SU4(u) => u.get_utry_grad_and_hessian(params, const_gates),
+            Gate::CSUM(c) => c.get_utry_grad_and_hessian(params, const_gates),
+            Gate::CZ(c) => c.get_utry_grad_and_hessian(params, const_gates),
+            Gate::CPhase(c) => c.get_utry_grad_and_hessian(params, const_gates),
+            Gate::CU3(c) => c.get_utry_grad_and_hessian(params, const_gates),
+            Gate::FSim(f) => f.get_utry_grad_and_hessian(params, const_gates),
+            Gate::PhasedXZ(p) => p.get_utry_grad_and_hessian(params, const_gates),
+            Gate::XXPlusYY(x) => x.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RXSubGate(x) => x.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RYSubGate(y) => y.get_utry_grad_and_hessian(params, const_gates),
+            Gate::RZSubGate(z) => z.get_utry_grad_and_hessian(params, const_gates),
+            Gate::SpecialUnitary(s) => s.get_utry_grad_and_hessian(params, const_gates),
+            Gate::PauliRotation(p) => p.get_utry_grad_and_hessian(params, const_gates),
+            Gate::VariableUnitary(v) => v.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Diagonal(d) => d.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Hamiltonian(h) => h.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Embedded(e) => e.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Controlled(c) => c.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Dagger(d) => d.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Frozen(f) => f.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Circuit(c) => c.get_utry_grad_and_hessian(params, const_gates),
+            Gate::Dynamic(d) => d.get_utry_grad_and_hessian(params, const_gates),
+        }
+    }
+}
+
 impl Size for Gate {
     fn num_qudits(&self) -> usize {
         match self {
             Gate::Constant(c) => c.num_qudits(),
+            Gate::Permutation(p) => p.num_qudits(),
             Gate::U1(_) => 1,
             Gate::U2(_) => 1,
             Gate::U3(_) => 1,
@@ -156,8 +361,27 @@ impl Size for Gate {
             Gate::CRX(_) => 2,
             Gate::CRY(_) => 2,
             Gate::CRZ(_) => 2,
+            Gate::SU4(_) => 2,
+            Gate::CSUM(_) => 2,
+            Gate::CZ(_) => 2,
+            Gate::CPhase(_) => 2,
+            Gate::CU3(_) => 2,
+            Gate::FSim(_) => 2,
+            Gate::PhasedXZ(_) => 1,
+            Gate::XXPlusYY(_) => 2,
+            Gate::RXSubGate(_) => 1,
+            Gate::RYSubGate(_) => 1,
             Gate::RZSubGate(_) => 1,
+            Gate::SpecialUnitary(_) => 1,
+            Gate::PauliRotation(p) => p.num_qudits(),
             Gate::VariableUnitary(v) => v.num_qudits(),
+            Gate::Diagonal(d) => d.num_qudits(),
+            Gate::Hamiltonian(h) => h.num_qudits(),
+            Gate::Embedded(e) => e.num_qudits(),
+            Gate::Controlled(c) => c.num_qudits(),
+            Gate::Dagger(d) => d.num_qudits(),
+            Gate::Frozen(f) => f.num_qudits(),
+            Gate::Circuit(c) => c.num_qudits(),
             Gate::Dynamic(d) => d.num_qudits(),
         }
     }
@@ -167,6 +391,7 @@ impl Optimize for Gate {
     fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
         match self {
             Gate::Constant(_) => todo!(),
+            Gate::Permutation(p) => p.optimize(env_matrix),
             Gate::U1(u) => u.optimize(env_matrix),
             Gate::U2(u) => u.optimize(env_matrix),
             Gate::U3(u) => u.optimize(env_matrix),
@@ -180,9 +405,70 @@ impl Optimize for Gate {
             Gate::CRX(x) => x.optimize(env_matrix),
             Gate::CRY(y) => y.optimize(env_matrix),
             Gate::CRZ(z) => z.optimize(env_matrix),
+            Gate::SU4(u) => u.optimize(env_matrix),
+            Gate::CSUM(c) => c.optimize(env_matrix),
+            Gate::CZ(c) => c.optimize(env_matrix),
+            Gate::CPhase(c) => c.optimize(env_matrix),
+            Gate::CU3(c) => c.optimize(env_matrix),
+            Gate::FSim(f) => f.optimize(env_matrix),
+            Gate::PhasedXZ(p) => p.optimize(env_matrix),
+            Gate::XXPlusYY(x) => x.optimize(env_matrix),
+            Gate::RXSubGate(x) => x.optimize(env_matrix),
+            Gate::RYSubGate(y) => y.optimize(env_matrix),
             Gate::RZSubGate(z) => z.optimize(env_matrix),
+            Gate::SpecialUnitary(s) => s.optimize(env_matrix),
+            Gate::PauliRotation(p) => p.optimize(env_matrix),
             Gate::VariableUnitary(v) => v.optimize(env_matrix),
+            Gate::Diagonal(d) => d.optimize(env_matrix),
+            Gate::Hamiltonian(h) => h.optimize(env_matrix),
+            Gate::Embedded(e) => e.optimize(env_matrix),
+            Gate::Controlled(c) => c.optimize(env_matrix),
+            Gate::Dagger(d) => d.optimize(env_matrix),
+            Gate::Frozen(f) => f.optimize(env_matrix),
+            Gate::Circuit(c) => c.optimize(env_matrix),
             Gate::Dynamic(d) => d.optimize(env_matrix),
         }
     }
+
+    fn can_optimize(&self) -> bool {
+        match self {
+            Gate::Constant(c) => c.can_optimize(),
+            Gate::Permutation(p) => p.can_optimize(),
+            Gate::U1(u) => u.can_optimize(),
+            Gate::U2(u) => u.can_optimize(),
+            Gate::U3(u) => u.can_optimize(),
+            Gate::U8(u) => u.can_optimize(),
+            Gate::RX(x) => x.can_optimize(),
+            Gate::RY(y) => y.can_optimize(),
+            Gate::RZ(z) => z.can_optimize(),
+            Gate::RXX(x) => x.can_optimize(),
+            Gate::RYY(y) => y.can_optimize(),
+            Gate::RZZ(z) => z.can_optimize(),
+            Gate::CRX(x) => x.can_optimize(),
+            Gate::CRY(y) => y.can_optimize(),
+            Gate::CRZ(z) => z.can_optimize(),
+            Gate::SU4(u) => u.can_optimize(),
+            Gate::CSUM(c) => c.can_optimize(),
+            Gate::CZ(c) => c.can_optimize(),
+            Gate::CPhase(c) => c.can_optimize(),
+            Gate::CU3(c) => c.can_optimize(),
+            Gate::FSim(f) => f.can_optimize(),
+            Gate::PhasedXZ(p) => p.can_optimize(),
+            Gate::XXPlusYY(x) => x.can_optimize(),
+            Gate::RXSubGate(x) => x.can_optimize(),
+            Gate::RYSubGate(y) => y.can_optimize(),
+            Gate::RZSubGate(z) => z.can_optimize(),
+            Gate::SpecialUnitary(s) => s.can_optimize(),
+            Gate::PauliRotation(p) => p.can_optimize(),
+            Gate::VariableUnitary(v) => v.can_optimize(),
+            Gate::Diagonal(d) => d.can_optimize(),
+            Gate::Hamiltonian(h) => h.can_optimize(),
+            Gate::Embedded(e) => e.can_optimize(),
+            Gate::Controlled(c) => c.can_optimize(),
+            Gate::Dagger(d) => d.can_optimize(),
+            Gate::Frozen(f) => f.can_optimize(),
+            Gate::Circuit(c) => c.can_optimize(),
+            Gate::Dynamic(d) => d.can_optimize(),
+        }
+    }
 }
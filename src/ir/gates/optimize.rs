@@ -7,4 +7,10 @@ pub trait Optimize {
     fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
         unimplemented!()
     }
+
+    /// Whether `optimize` is implemented. QFactor checks this for every
+    /// parameterized gate before it starts sweeping.
+    fn can_optimize(&self) -> bool {
+        true
+    }
 }
@@ -1,4 +1,4 @@
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
 use crate::{i, r};
 
@@ -83,6 +83,8 @@ impl Gradient for CRXGate {
     }
 }
 
+impl Hessian for CRXGate {}
+
 impl Size for CRXGate {
     fn num_qudits(&self) -> usize {
         2
@@ -90,7 +92,9 @@ impl Size for CRXGate {
 }
 
 impl Optimize for CRXGate {
-    fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
-        unimplemented!()
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let re = (env_matrix[[2, 2]] + env_matrix[[3, 3]]).re;
+        let im = (env_matrix[[2, 3]] + env_matrix[[3, 2]]).im;
+        vec![2. * im.atan2(re)]
     }
 }
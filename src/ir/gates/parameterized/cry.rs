@@ -1,6 +1,6 @@
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
-use crate::{i, r};
+use crate::r;
 
 use ndarray::{Array2, Array3, ArrayViewMut2};
 use ndarray_linalg::c64;
@@ -39,7 +39,7 @@ impl Unitary for CRYGate {
 impl Gradient for CRYGate {
     fn get_grad(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
         let dcos = -1. * r!(params[0] / 2.).sin() / 2.;
-        let dsin = i!(-1.) * r!((params[0] / 2.).cos() / 2.);
+        let dsin = r!((params[0] / 2.).cos() / 2.);
         let zero = r!(0.0);
         Array3::from_shape_vec(
             (1, 4, 4),
@@ -59,7 +59,7 @@ impl Gradient for CRYGate {
         let cos = r!((params[0] / 2.).cos());
         let sin = r!((params[0] / 2.).sin());
         let dcos = -1. * r!(params[0] / 2.).sin() / 2.;
-        let dsin = i!(-1.) * r!((params[0] / 2.).cos() / 2.);
+        let dsin = r!((params[0] / 2.).cos() / 2.);
         let zero = r!(0.0);
         let one = r!(1.0);
 
@@ -84,6 +84,8 @@ impl Gradient for CRYGate {
     }
 }
 
+impl Hessian for CRYGate {}
+
 impl Size for CRYGate {
     fn num_qudits(&self) -> usize {
         2
@@ -91,7 +93,9 @@ impl Size for CRYGate {
 }
 
 impl Optimize for CRYGate {
-    fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
-        unimplemented!()
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let diag = (env_matrix[[2, 2]] + env_matrix[[3, 3]]).re;
+        let off_diag = (env_matrix[[2, 3]] - env_matrix[[3, 2]]).re;
+        vec![2. * off_diag.atan2(diag)]
     }
 }
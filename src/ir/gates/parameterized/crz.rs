@@ -1,4 +1,4 @@
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
 use crate::{i, r};
 
@@ -84,6 +84,8 @@ impl Gradient for CRZGate {
     }
 }
 
+impl Hessian for CRZGate {}
+
 impl Size for CRZGate {
     fn num_qudits(&self) -> usize {
         2
@@ -91,7 +93,9 @@ impl Size for CRZGate {
 }
 
 impl Optimize for CRZGate {
-    fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
-        unimplemented!()
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let re = (env_matrix[[2, 2]] + env_matrix[[3, 3]]).re;
+        let im = (env_matrix[[2, 2]] - env_matrix[[3, 3]]).im;
+        vec![2. * im.atan2(re)]
     }
 }
@@ -0,0 +1,86 @@
+use crate::ir::gates::U3Gate;
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use ndarray::{s, Array2, Array3, ArrayView2, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// IBM's controlled U3 gate, applying U3 to the second qubit when the first is |1>
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct CU3Gate();
+
+impl CU3Gate {
+    pub fn new() -> Self {
+        CU3Gate {}
+    }
+
+    /// Find the parameters that maximize `|offset + Tr(env_matrix @ U)|`.
+    ///
+    /// The |0> control block is constant and adds `Tr(E[0:2, 0:2])` to the
+    /// trace, so U3 is optimized against the |1> block with that added to `offset`.
+    pub(crate) fn optimize_with_offset(
+        &self,
+        env_matrix: ArrayView2<c64>,
+        offset: c64,
+    ) -> Vec<f64> {
+        let offset = offset + env_matrix.slice(s![..2, ..2]).diag().sum();
+        U3Gate::new().optimize_with_offset(env_matrix.slice(s![2.., 2..]), offset)
+    }
+}
+
+impl Unitary for CU3Gate {
+    fn num_params(&self) -> usize {
+        3
+    }
+
+    fn get_utry(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let mut utry = Array2::eye(4);
+        utry.slice_mut(s![2.., 2..])
+            .assign(&U3Gate::new().get_utry(params, const_gates));
+        utry
+    }
+}
+
+impl Gradient for CU3Gate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let (target, target_grad) = U3Gate::new().get_utry_and_grad(params, const_gates);
+        let mut utry = Array2::eye(4);
+        utry.slice_mut(s![2.., 2..]).assign(&target);
+        let mut grad = Array3::zeros((3, 4, 4));
+        grad.slice_mut(s![.., 2.., 2..]).assign(&target_grad);
+        (utry, grad)
+    }
+}
+
+impl Hessian for CU3Gate {}
+
+impl Size for CU3Gate {
+    fn num_qudits(&self) -> usize {
+        2
+    }
+}
+
+impl Optimize for CU3Gate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        self.optimize_with_offset(env_matrix.view(), c64::new(0.0, 0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+
+    #[test]
+    fn optimize_reaches_numeric_maximum() {
+        assert_optimize_reaches_numeric_max(&CU3Gate::new().into(), 14);
+    }
+}
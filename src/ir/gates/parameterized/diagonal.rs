@@ -0,0 +1,82 @@
+use crate::i;
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use ndarray::{Array1, Array2, Array3, Array4, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// A diagonal n-qudit gate with an independent phase on each basis state
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct DiagonalGate {
+    size: usize,
+    radixes: Vec<usize>,
+    dim: usize,
+}
+
+impl DiagonalGate {
+    pub fn new(size: usize, radixes: Vec<usize>) -> Self {
+        let dim = radixes.iter().product();
+        DiagonalGate { size, radixes, dim }
+    }
+
+    /// Calculate the diagonal of the gate's unitary.
+    pub fn get_diagonal(&self, params: &[f64]) -> Array1<c64> {
+        params.iter().map(|&phase| i!(phase).exp()).collect()
+    }
+}
+
+impl Unitary for DiagonalGate {
+    fn num_params(&self) -> usize {
+        self.dim
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        Array2::from_diag(&self.get_diagonal(params))
+    }
+}
+
+impl Gradient for DiagonalGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        _const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let diagonal = self.get_diagonal(params);
+        let mut grad = Array3::zeros((self.dim, self.dim, self.dim));
+        for (k, &entry) in diagonal.iter().enumerate() {
+            grad[[k, k, k]] = i!(1.0) * entry;
+        }
+        (Array2::from_diag(&diagonal), grad)
+    }
+}
+
+impl Hessian for DiagonalGate {
+    /// Each phase only moves its own diagonal entry, so the Hessian is zero off
+    /// the diagonal in parameter space.
+    fn get_hessian(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array4<c64> {
+        let diagonal = self.get_diagonal(params);
+        let mut hess = Array4::zeros((self.dim, self.dim, self.dim, self.dim));
+        for (k, &entry) in diagonal.iter().enumerate() {
+            hess[[k, k, k, k]] = -entry;
+        }
+        hess
+    }
+}
+
+impl Size for DiagonalGate {
+    fn num_qudits(&self) -> usize {
+        self.size
+    }
+}
+
+impl Optimize for DiagonalGate {
+    /// Each phase only multiplies one diagonal entry of the environment, so it
+    /// is chosen to cancel that entry's phase.
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        env_matrix.diag().iter().map(|x| -x.arg()).collect()
+    }
+}
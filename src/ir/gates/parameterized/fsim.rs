@@ -0,0 +1,85 @@
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::{i, r};
+
+use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// Google's fermionic simulation gate, an iSWAP-like rotation by `θ` on
+/// span{|01>, |10>} followed by a controlled phase of `-φ` on |11>
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct FSimGate();
+
+impl FSimGate {
+    pub fn new() -> Self {
+        FSimGate {}
+    }
+}
+
+impl Unitary for FSimGate {
+    fn num_params(&self) -> usize {
+        2
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let cos = r!(params[0].cos());
+        let sin = i!(-params[0].sin());
+        let phase = i!(-params[1]).exp();
+        let zero = r!(0.0);
+        let one = r!(1.0);
+        Array2::from_shape_vec(
+            (4, 4),
+            vec![
+                one, zero, zero, zero, zero, cos, sin, zero, zero, sin, cos, zero, zero, zero,
+                zero, phase,
+            ],
+        )
+        .unwrap()
+    }
+}
+
+impl Gradient for FSimGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let dcos = r!(-params[0].sin());
+        let dsin = i!(-params[0].cos());
+        let dphase = i!(-1.) * i!(-params[1]).exp();
+        let zero = r!(0.0);
+        (
+            self.get_utry(params, const_gates),
+            Array3::from_shape_vec(
+                (2, 4, 4),
+                vec![
+                    zero, zero, zero, zero, zero, dcos, dsin, zero, zero, dsin, dcos, zero, zero,
+                    zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, zero,
+                    zero, zero, zero, zero, zero, dphase,
+                ],
+            )
+            .unwrap(),
+        )
+    }
+}
+
+impl Hessian for FSimGate {}
+
+impl Size for FSimGate {
+    fn num_qudits(&self) -> usize {
+        2
+    }
+}
+
+impl Optimize for FSimGate {
+    /// The two parameters act on disjoint blocks, so each is maximized separately.
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let re = (env_matrix[[1, 1]] + env_matrix[[2, 2]]).re;
+        let im = (env_matrix[[1, 2]] + env_matrix[[2, 1]]).im;
+        vec![im.atan2(re), env_matrix[[3, 3]].arg()]
+    }
+}
@@ -0,0 +1,114 @@
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::squaremat::*;
+use crate::{i, r};
+
+use ndarray::{s, Array1, Array2, Array3, ShapeBuilder};
+use ndarray_linalg::{c64, Eigh, UPLO};
+
+/// A gate representing `exp(-i Σ θ_k H_k)` for a set of Hermitian generators `H_k`
+///
+/// The generators are looked up by index in the constant gate list, so one
+/// definition can be shared between circuits.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct HamiltonianGate {
+    size: usize,
+    radixes: Vec<usize>,
+    dim: usize,
+    generators: Vec<usize>,
+}
+
+impl HamiltonianGate {
+    pub fn new(size: usize, radixes: Vec<usize>, generators: Vec<usize>) -> Self {
+        let dim = radixes.iter().product();
+        HamiltonianGate {
+            size,
+            radixes,
+            dim,
+            generators,
+        }
+    }
+
+    /// Diagonalize `Σ θ_k H_k`, returning its eigenvalues and eigenvectors.
+    fn eigh(&self, params: &[f64], const_gates: &[Array2<c64>]) -> (Array1<f64>, Array2<c64>) {
+        assert_eq!(self.num_params(), params.len());
+        // `eigh` transposes C-ordered input without conjugating it, so build in Fortran order
+        let mut hamiltonian = Array2::zeros((self.dim, self.dim).f());
+        for (&param, &index) in params.iter().zip(&self.generators) {
+            hamiltonian.scaled_add(r!(param), &const_gates[index]);
+        }
+        hamiltonian
+            .eigh(UPLO::Lower)
+            .expect("Failed to diagonalize Hamiltonian")
+    }
+}
+
+impl Unitary for HamiltonianGate {
+    fn num_params(&self) -> usize {
+        self.generators.len()
+    }
+
+    fn get_utry(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let (eigvals, eigvecs) = self.eigh(params, const_gates);
+        let phased = &eigvecs * &eigvals.mapv(|e| i!(-e).exp());
+        phased.matmul(eigvecs.t().conj().view())
+    }
+}
+
+impl Gradient for HamiltonianGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    /// The Fréchet derivative of `exp(-iH)` along `H_k` is `V (Γ ∘ V^† H_k V) V^†`,
+    /// where `Γ_ab = (e^{-iλ_a} - e^{-iλ_b}) / (λ_a - λ_b)` is computed in the
+    /// form `-i e^{-i(λ_a + λ_b)/2} sinc((λ_a - λ_b)/2)`, which is exact for
+    /// degenerate eigenvalues.
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let (eigvals, eigvecs) = self.eigh(params, const_gates);
+        let eigvecs_h = eigvecs.t().conj();
+        let phased = &eigvecs * &eigvals.mapv(|e| i!(-e).exp());
+        let utry = phased.matmul(eigvecs_h.view());
+
+        let gamma = Array2::from_shape_fn((self.dim, self.dim), |(a, b)| {
+            let half_diff = (eigvals[a] - eigvals[b]) / 2.;
+            let sinc = if half_diff == 0. {
+                1.
+            } else {
+                half_diff.sin() / half_diff
+            };
+            i!(-1.) * i!(-(eigvals[a] + eigvals[b]) / 2.).exp() * sinc
+        });
+        let mut grad = Array3::zeros((self.num_params(), self.dim, self.dim));
+        for (k, &index) in self.generators.iter().enumerate() {
+            let rotated = eigvecs_h
+                .matmul(const_gates[index].view())
+                .matmul(eigvecs.view());
+            let d_utry = eigvecs
+                .matmul((&gamma * &rotated).view())
+                .matmul(eigvecs_h.view());
+            grad.slice_mut(s![k, .., ..]).assign(&d_utry);
+        }
+        (utry, grad)
+    }
+}
+
+impl Hessian for HamiltonianGate {}
+
+impl Size for HamiltonianGate {
+    fn num_qudits(&self) -> usize {
+        self.size
+    }
+}
+
+/// The generators live in the constant gates, which `optimize` is not given,
+/// so there is no update for this gate.
+impl Optimize for HamiltonianGate {
+    fn can_optimize(&self) -> bool {
+        false
+    }
+}
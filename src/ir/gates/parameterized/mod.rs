@@ -1,31 +1,83 @@
 mod crx;
 mod cry;
 mod crz;
+mod cu3;
+mod diagonal;
+mod fsim;
+mod hamiltonian;
+mod pauli;
+mod phased_xz;
 mod rx;
+mod rxsub;
 mod rxx;
 mod ry;
+mod rysub;
 mod ryy;
 mod rz;
 mod rzz;
+mod special_unitary;
+mod su4;
 mod u1;
 mod u2;
 mod u3;
 mod u8;
 mod variable;
+mod xx_plus_yy;
 mod rzsub;
 
 pub use self::u8::U8Gate;
 pub use crx::CRXGate;
 pub use cry::CRYGate;
 pub use crz::CRZGate;
+pub use cu3::CU3Gate;
+pub use diagonal::DiagonalGate;
+pub use fsim::FSimGate;
+pub use hamiltonian::HamiltonianGate;
+pub use pauli::PauliRotationGate;
+pub use phased_xz::PhasedXZGate;
 pub use rx::RXGate;
+pub use rxsub::RXSubGate;
 pub use rxx::RXXGate;
 pub use ry::RYGate;
+pub use rysub::RYSubGate;
 pub use ryy::RYYGate;
 pub use rz::RZGate;
 pub use rzz::RZZGate;
+pub use special_unitary::SpecialUnitaryGate;
+pub use su4::SU4Gate;
 pub use u1::U1Gate;
 pub use u2::U2Gate;
 pub use u3::U3Gate;
 pub use rzsub::RZSubGate;
 pub use variable::VariableUnitaryGate;
+pub use xx_plus_yy::XXPlusYYGate;
+
+use ndarray::{Array2, Array3, ArrayView2, ArrayView3, ArrayViewMut2, Axis};
+use ndarray_linalg::c64;
+
+/// Place a 2x2 `matrix` on `levels` of `base`.
+fn place_on_levels(mut base: ArrayViewMut2<c64>, levels: [usize; 2], matrix: ArrayView2<c64>) {
+    for (r, &row) in levels.iter().enumerate() {
+        for (c, &col) in levels.iter().enumerate() {
+            base[[row, col]] = matrix[[r, c]];
+        }
+    }
+}
+
+/// Embed a 2x2 unitary acting on `levels` of a qudit with `radix` levels,
+/// leaving the other levels unchanged.
+fn embed_levels_utry(radix: usize, levels: [usize; 2], utry: ArrayView2<c64>) -> Array2<c64> {
+    let mut unitary = Array2::eye(radix);
+    place_on_levels(unitary.view_mut(), levels, utry);
+    unitary
+}
+
+/// Embed the gradient of a 2x2 unitary acting on `levels` of a qudit with
+/// `radix` levels.
+fn embed_levels_grad(radix: usize, levels: [usize; 2], grad: ArrayView3<c64>) -> Array3<c64> {
+    let mut embedded = Array3::zeros((grad.len_of(Axis(0)), radix, radix));
+    for (d_utry, mut out) in grad.outer_iter().zip(embedded.outer_iter_mut()) {
+        place_on_levels(out.view_mut(), levels, d_utry);
+    }
+    embedded
+}
@@ -0,0 +1,120 @@
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::{i, r};
+
+use ndarray::{Array2, Array3, ArrayViewMut2, Axis};
+use ndarray_linalg::c64;
+
+/// A gate representing the rotation `exp(-iθP)` about an arbitrary n-qubit Pauli string `P`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PauliRotationGate {
+    size: usize,
+    dim: usize,
+    rows: Vec<usize>,
+    phases: Vec<c64>,
+}
+
+impl PauliRotationGate {
+    /// Build the gate for a Pauli string such as `"XZIY"`, where the first
+    /// character acts on the first (most significant) qubit.
+    pub fn new(pauli: &str) -> Self {
+        let size = pauli.len();
+        let dim = 1 << size;
+        let mut rows = Vec::with_capacity(dim);
+        let mut phases = Vec::with_capacity(dim);
+        for col in 0..dim {
+            let mut row = col;
+            let mut phase = r!(1.0);
+            for (q, p) in pauli.chars().enumerate() {
+                let shift = size - 1 - q;
+                let bit = (col >> shift) & 1;
+                match p {
+                    'I' => (),
+                    'X' => row ^= 1 << shift,
+                    'Y' => {
+                        row ^= 1 << shift;
+                        phase *= if bit == 0 { i!(1.0) } else { i!(-1.0) };
+                    }
+                    'Z' => {
+                        if bit == 1 {
+                            phase = -phase;
+                        }
+                    }
+                    _ => panic!("Invalid Pauli string {}", pauli),
+                }
+            }
+            rows.push(row);
+            phases.push(phase);
+        }
+        PauliRotationGate {
+            size,
+            dim,
+            rows,
+            phases,
+        }
+    }
+
+    /// Calculate `a I + b P`.
+    fn combine(&self, a: c64, b: c64) -> Array2<c64> {
+        let mut matrix = Array2::zeros((self.dim, self.dim));
+        for (col, (&row, &phase)) in self.rows.iter().zip(&self.phases).enumerate() {
+            matrix[[col, col]] += a;
+            matrix[[row, col]] += b * phase;
+        }
+        matrix
+    }
+}
+
+impl Unitary for PauliRotationGate {
+    fn num_params(&self) -> usize {
+        1
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let (sin, cos) = params[0].sin_cos();
+        self.combine(r!(cos), i!(-sin))
+    }
+}
+
+impl Gradient for PauliRotationGate {
+    fn get_grad(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
+        let (sin, cos) = params[0].sin_cos();
+        self.combine(r!(-sin), i!(-cos)).insert_axis(Axis(0))
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        _const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let (sin, cos) = params[0].sin_cos();
+        (
+            self.combine(r!(cos), i!(-sin)),
+            self.combine(r!(-sin), i!(-cos)).insert_axis(Axis(0)),
+        )
+    }
+}
+
+impl Hessian for PauliRotationGate {}
+
+impl Size for PauliRotationGate {
+    fn num_qudits(&self) -> usize {
+        self.size
+    }
+}
+
+impl Optimize for PauliRotationGate {
+    /// `Re Tr(E U) = cos(θ) Re Tr(E) + sin(θ) Im Tr(E P)`
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let re = env_matrix.diag().sum().re;
+        let im = self
+            .rows
+            .iter()
+            .zip(&self.phases)
+            .enumerate()
+            .map(|(col, (&row, &phase))| env_matrix[[col, row]] * phase)
+            .sum::<c64>()
+            .im;
+        vec![im.atan2(re)]
+    }
+}
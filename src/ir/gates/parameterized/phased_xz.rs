@@ -0,0 +1,123 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::ir::gates::utils::optimal_unitary;
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::{i, r};
+
+use ndarray::{Array2, Array3, ArrayView2, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// Cirq's PhasedXZ single qubit gate `Z^z Z^a X^x Z^-a`
+///
+/// The parameters are the exponents `[x, z, a]`, measured in half turns.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct PhasedXZGate();
+
+impl PhasedXZGate {
+    pub fn new() -> Self {
+        PhasedXZGate {}
+    }
+}
+
+impl Unitary for PhasedXZGate {
+    fn num_params(&self) -> usize {
+        3
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let (x, z, a) = (params[0], params[1], params[2]);
+        let global = i!(FRAC_PI_2 * x).exp();
+        let cos = global * (FRAC_PI_2 * x).cos();
+        let sin = global * i!(-(FRAC_PI_2 * x).sin());
+        Array2::from_shape_vec(
+            (2, 2),
+            vec![
+                cos,
+                sin * i!(-PI * a).exp(),
+                sin * i!(PI * (z + a)).exp(),
+                cos * i!(PI * z).exp(),
+            ],
+        )
+        .unwrap()
+    }
+}
+
+impl Gradient for PhasedXZGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let (x, z, a) = (params[0], params[1], params[2]);
+        let utry = self.get_utry(params, const_gates);
+        let global = i!(FRAC_PI_2 * x).exp();
+        let (c, s) = ((FRAC_PI_2 * x).cos(), (FRAC_PI_2 * x).sin());
+        let dcos = FRAC_PI_2 * global * c64::new(-s, c);
+        let dsin = FRAC_PI_2 * global * c64::new(s, -c);
+        let zero = r!(0.0);
+        let ipi = i!(PI);
+        (
+            utry.clone(),
+            Array3::from_shape_vec(
+                (3, 2, 2),
+                vec![
+                    // param 0
+                    dcos,
+                    dsin * i!(-PI * a).exp(),
+                    dsin * i!(PI * (z + a)).exp(),
+                    dcos * i!(PI * z).exp(),
+                    // param 1
+                    zero,
+                    zero,
+                    ipi * utry[[1, 0]],
+                    ipi * utry[[1, 1]],
+                    // param 2
+                    zero,
+                    -ipi * utry[[0, 1]],
+                    ipi * utry[[1, 0]],
+                    zero,
+                ],
+            )
+            .unwrap(),
+        )
+    }
+}
+
+impl Hessian for PhasedXZGate {}
+
+impl Size for PhasedXZGate {
+    fn num_qudits(&self) -> usize {
+        1
+    }
+}
+
+impl Optimize for PhasedXZGate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let utry = optimal_unitary(env_matrix);
+        phased_xz_params(utry.view())
+    }
+}
+
+/// Recover the PhasedXZ exponents of a 2x2 unitary, discarding its global phase.
+///
+/// Each phase is read from a combination of entries whose magnitudes sum to one,
+/// which keeps the extraction stable near `x = 0` and `x = 1`.
+fn phased_xz_params(utry: ArrayView2<c64>) -> Vec<f64> {
+    let (u00, u01, u10, u11) = (utry[[0, 0]], utry[[0, 1]], utry[[1, 0]], utry[[1, 1]]);
+    let x = 2. * (u01.norm() + u10.norm()).atan2(u00.norm() + u11.norm()) / PI;
+    // conj(u01) u00 and -u10 conj(u11) both have phase π/2 + πa
+    let a_phase = u01.conj() * u00 - u10 * u11.conj();
+    let a = if a_phase.norm() > 1e-12 {
+        (a_phase.arg() - FRAC_PI_2) / PI
+    } else {
+        0.
+    };
+    // u11 conj(u00) and u10 conj(u01) exp(-2πia) both have phase πz
+    let z_phase = u11 * u00.conj() + u10 * u01.conj() * i!(-2. * PI * a).exp();
+    vec![x, z_phase.arg() / PI, a]
+}
@@ -1,8 +1,8 @@
 use crate::ir::gates::utils::{rot_x, rot_x_jac};
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
 
-use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2, Axis};
 use ndarray_linalg::c64;
 
 /// Arbitrary X rotation single qubit gate
@@ -39,6 +39,15 @@ impl Gradient for RXGate {
     }
 }
 
+impl Hessian for RXGate {
+    /// `RX(θ) = exp(-iθX/2)` and `X² = I`, so the second derivative is a quarter of `-U`.
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        (self.get_utry(params, const_gates) * c64::new(-0.25, 0.0))
+            .insert_axis(Axis(0))
+            .insert_axis(Axis(0))
+    }
+}
+
 impl Size for RXGate {
     fn num_qudits(&self) -> usize {
         1
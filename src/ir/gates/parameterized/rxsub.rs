@@ -0,0 +1,78 @@
+use crate::ir::gates::utils::{rot_x, rot_x_jac};
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use super::{embed_levels_grad, embed_levels_utry};
+
+use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// Arbitrary X rotation between two levels of a single qudit
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct RXSubGate {
+    radix: usize,
+    level1: usize,
+    level2: usize,
+}
+
+impl RXSubGate {
+    pub fn new(radix: usize, level1: usize, level2: usize) -> Self {
+        RXSubGate {
+            radix,
+            level1,
+            level2,
+        }
+    }
+}
+
+impl Unitary for RXSubGate {
+    fn num_params(&self) -> usize {
+        1
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        embed_levels_utry(
+            self.radix,
+            [self.level1, self.level2],
+            rot_x(params[0]).view(),
+        )
+    }
+}
+
+impl Gradient for RXSubGate {
+    fn get_grad(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
+        embed_levels_grad(
+            self.radix,
+            [self.level1, self.level2],
+            rot_x_jac(params[0]).view(),
+        )
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        (
+            self.get_utry(params, const_gates),
+            self.get_grad(params, const_gates),
+        )
+    }
+}
+
+impl Hessian for RXSubGate {}
+
+impl Size for RXSubGate {
+    fn num_qudits(&self) -> usize {
+        1
+    }
+}
+
+impl Optimize for RXSubGate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let (l1, l2) = (self.level1, self.level2);
+        let re = (env_matrix[[l1, l1]] + env_matrix[[l2, l2]]).re;
+        let im = (env_matrix[[l1, l2]] + env_matrix[[l2, l1]]).im;
+        vec![2. * im.atan2(re)]
+    }
+}
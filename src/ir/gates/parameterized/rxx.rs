@@ -1,8 +1,8 @@
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
 use crate::{i, r};
 
-use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2, Axis};
 use ndarray_linalg::c64;
 
 /// A gate representing an arbitrary rotation around the XX axis
@@ -81,6 +81,15 @@ impl Gradient for RXXGate {
     }
 }
 
+impl Hessian for RXXGate {
+    /// `RXX(θ) = exp(-iθXX/2)` and `(XX)² = I`, so the second derivative is a quarter of `-U`.
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        (self.get_utry(params, const_gates) * c64::new(-0.25, 0.0))
+            .insert_axis(Axis(0))
+            .insert_axis(Axis(0))
+    }
+}
+
 impl Size for RXXGate {
     fn num_qudits(&self) -> usize {
         2
@@ -0,0 +1,78 @@
+use crate::ir::gates::utils::{rot_y, rot_y_jac};
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use super::{embed_levels_grad, embed_levels_utry};
+
+use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// Arbitrary Y rotation between two levels of a single qudit
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct RYSubGate {
+    radix: usize,
+    level1: usize,
+    level2: usize,
+}
+
+impl RYSubGate {
+    pub fn new(radix: usize, level1: usize, level2: usize) -> Self {
+        RYSubGate {
+            radix,
+            level1,
+            level2,
+        }
+    }
+}
+
+impl Unitary for RYSubGate {
+    fn num_params(&self) -> usize {
+        1
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        embed_levels_utry(
+            self.radix,
+            [self.level1, self.level2],
+            rot_y(params[0]).view(),
+        )
+    }
+}
+
+impl Gradient for RYSubGate {
+    fn get_grad(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
+        embed_levels_grad(
+            self.radix,
+            [self.level1, self.level2],
+            rot_y_jac(params[0]).view(),
+        )
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        (
+            self.get_utry(params, const_gates),
+            self.get_grad(params, const_gates),
+        )
+    }
+}
+
+impl Hessian for RYSubGate {}
+
+impl Size for RYSubGate {
+    fn num_qudits(&self) -> usize {
+        1
+    }
+}
+
+impl Optimize for RYSubGate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let (l1, l2) = (self.level1, self.level2);
+        let diag = (env_matrix[[l1, l1]] + env_matrix[[l2, l2]]).re;
+        let off_diag = (env_matrix[[l1, l2]] - env_matrix[[l2, l1]]).re;
+        vec![2. * off_diag.atan2(diag)]
+    }
+}
@@ -1,8 +1,8 @@
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
 use crate::{i, r};
 
-use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2, Axis};
 use ndarray_linalg::c64;
 
 /// A gate representing an arbitrary rotation around the YY axis
@@ -86,6 +86,15 @@ impl Gradient for RYYGate {
     }
 }
 
+impl Hessian for RYYGate {
+    /// `RYY(θ) = exp(-iθYY/2)` and `(YY)² = I`, so the second derivative is a quarter of `-U`.
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        (self.get_utry(params, const_gates) * c64::new(-0.25, 0.0))
+            .insert_axis(Axis(0))
+            .insert_axis(Axis(0))
+    }
+}
+
 impl Size for RYYGate {
     fn num_qudits(&self) -> usize {
         2
@@ -93,7 +102,10 @@ impl Size for RYYGate {
 }
 
 impl Optimize for RYYGate {
-    fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
-        unimplemented!()
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let re = env_matrix.diag().sum().re;
+        let im =
+            (env_matrix[[1, 2]] + env_matrix[[2, 1]] - env_matrix[[0, 3]] - env_matrix[[3, 0]]).im;
+        vec![2. * im.atan2(re)]
     }
 }
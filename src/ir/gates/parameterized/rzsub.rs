@@ -1,16 +1,15 @@
-use std::f64::consts::PI;
-
-use crate::i;
 use crate::ir::gates::utils::{rot_z, rot_z_jac};
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
 
+use super::{embed_levels_grad, embed_levels_utry};
+
 use ndarray::{Array2, Array3, ArrayViewMut2};
 use ndarray_linalg::c64;
 
-/// Arbitrary Y rotation single qubit gate
+/// Arbitrary Z rotation between two levels of a single qudit
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
-pub struct RZSubGate{
+pub struct RZSubGate {
     radix: usize,
     level1: usize,
     level2: usize,
@@ -19,9 +18,9 @@ pub struct RZSubGate{
 impl RZSubGate {
     pub fn new(radix: usize, level1: usize, level2: usize) -> Self {
         RZSubGate {
-            radix: radix,
-            level1: level1,
-            level2: level2,
+            radix,
+            level1,
+            level2,
         }
     }
 }
@@ -32,49 +31,37 @@ impl Unitary for RZSubGate {
     }
 
     fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
-        let pexp = i!(0.5 * params[0]).exp();
-        let nexp = i!(-0.5 * params[0]).exp();
-
-        let mut unitary = Array2::eye(self.radix);
-        unitary[[self.level1, self.level1]] = nexp;
-        unitary[[self.level2, self.level2]] = pexp;
-        unitary
+        embed_levels_utry(
+            self.radix,
+            [self.level1, self.level2],
+            rot_z(params[0], None).view(),
+        )
     }
 }
 
 impl Gradient for RZSubGate {
     fn get_grad(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
-        let dpexp = i!(0.5) * i!(0.5 * params[0]).exp();
-        let dnexp = i!(-0.5) * i!(-0.5 * params[0]).exp();
-
-        let mut grad = Array3::zeros((1, self.radix, self.radix));
-        grad[[0, self.level1, self.level1]] = dnexp;
-        grad[[0, self.level2, self.level2]] = dpexp;
-        grad
+        embed_levels_grad(
+            self.radix,
+            [self.level1, self.level2],
+            rot_z_jac(params[0], None).view(),
+        )
     }
 
     fn get_utry_and_grad(
         &self,
         params: &[f64],
-        _const_gates: &[Array2<c64>],
+        const_gates: &[Array2<c64>],
     ) -> (Array2<c64>, Array3<c64>) {
-        let pexp = i!(0.5 * params[0]).exp();
-        let nexp = i!(-0.5 * params[0]).exp();
-        let dpexp = i!(0.5) * pexp;
-        let dnexp = i!(-0.5) * nexp;
-
-        let mut unitary = Array2::eye(self.radix);
-        unitary[[self.level1, self.level1]] = nexp;
-        unitary[[self.level2, self.level2]] = pexp;
-
-        let mut grad = Array3::zeros((1, self.radix, self.radix));
-        grad[[0, self.level1, self.level1]] = dnexp;
-        grad[[0, self.level2, self.level2]] = dpexp;
-
-        (unitary, grad)
+        (
+            self.get_utry(params, const_gates),
+            self.get_grad(params, const_gates),
+        )
     }
 }
 
+impl Hessian for RZSubGate {}
+
 impl Size for RZSubGate {
     fn num_qudits(&self) -> usize {
         1
@@ -83,6 +70,10 @@ impl Size for RZSubGate {
 
 impl Optimize for RZSubGate {
     fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
-        unimplemented!()
+        let a = env_matrix[[self.level1, self.level1]];
+        let b = env_matrix[[self.level2, self.level2]];
+        let re = (a + b).re;
+        let im = (a - b).im;
+        vec![2. * im.atan2(re)]
     }
 }
@@ -1,8 +1,8 @@
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
 use crate::{i, r};
 
-use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2, Axis};
 use ndarray_linalg::c64;
 
 /// A gate representing an arbitrary rotation around the ZZ axis
@@ -82,6 +82,15 @@ impl Gradient for RZZGate {
     }
 }
 
+impl Hessian for RZZGate {
+    /// `RZZ(θ) = exp(-iθZZ/2)` and `(ZZ)² = I`, so the second derivative is a quarter of `-U`.
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        (self.get_utry(params, const_gates) * c64::new(-0.25, 0.0))
+            .insert_axis(Axis(0))
+            .insert_axis(Axis(0))
+    }
+}
+
 impl Size for RZZGate {
     fn num_qudits(&self) -> usize {
         2
@@ -89,7 +98,10 @@ impl Size for RZZGate {
 }
 
 impl Optimize for RZZGate {
-    fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
-        unimplemented!()
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let re = env_matrix.diag().sum().re;
+        let im =
+            (env_matrix[[0, 0]] + env_matrix[[3, 3]] - env_matrix[[1, 1]] - env_matrix[[2, 2]]).im;
+        vec![2. * im.atan2(re)]
     }
 }
@@ -0,0 +1,207 @@
+use crate::i;
+use crate::ir::gates::utils::{optimal_unitary, rot_y, rot_y_jac, rot_z, rot_z_jac};
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::squaremat::*;
+
+use ndarray::{s, Array1, Array2, Array3, ArrayView2, ArrayViewMut2, Axis};
+use ndarray_linalg::{c64, Determinant};
+
+/// An arbitrary SU(d) gate on a single qudit, composed of Givens rotations
+///
+/// The unitary is `D G_N ... G_1`, where `D` is the product of `RZSubGate(0, k)`
+/// rotations for `k = 1..d` and each `G_k = RYSubGate(θ_k) RZSubGate(φ_k)` acts
+/// on a pair of levels. The parameters are the `d - 1` angles of `D` followed by
+/// `(θ_k, φ_k)` for each pair.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct SpecialUnitaryGate {
+    radix: usize,
+    pairs: Vec<(usize, usize)>,
+}
+
+impl SpecialUnitaryGate {
+    pub fn new(radix: usize) -> Self {
+        let pairs = (1..radix)
+            .rev()
+            .flat_map(|row| (0..row).map(move |col| (col, row)))
+            .collect();
+        SpecialUnitaryGate { radix, pairs }
+    }
+
+    /// Calculate the 2x2 block of `RYSubGate(θ) RZSubGate(φ)`.
+    fn givens(params: &[f64]) -> Array2<c64> {
+        rot_y(params[0]).matmul(rot_z(params[1], None).view())
+    }
+
+    fn givens_jac(params: &[f64]) -> [Array2<c64>; 2] {
+        [
+            rot_y_jac(params[0])
+                .slice(s![0, .., ..])
+                .matmul(rot_z(params[1], None).view()),
+            rot_y(params[0]).matmul(rot_z_jac(params[1], None).slice(s![0, .., ..])),
+        ]
+    }
+
+    /// The phases `D` applies to each level.
+    fn phases(&self, params: &[f64]) -> Vec<f64> {
+        let mut phases = vec![0.0; self.radix];
+        for (k, &alpha) in params[..self.radix - 1].iter().enumerate() {
+            phases[0] -= alpha / 2.;
+            phases[k + 1] += alpha / 2.;
+        }
+        phases
+    }
+
+    /// Left multiply `matrix` by a 2x2 block acting on the levels `pair`.
+    fn apply_rows(matrix: &mut Array2<c64>, pair: (usize, usize), block: ArrayView2<c64>) {
+        let (top, bottom) = (matrix.row(pair.0).to_owned(), matrix.row(pair.1).to_owned());
+        matrix
+            .row_mut(pair.0)
+            .assign(&(&top * block[[0, 0]] + &bottom * block[[0, 1]]));
+        matrix
+            .row_mut(pair.1)
+            .assign(&(&top * block[[1, 0]] + &bottom * block[[1, 1]]));
+    }
+
+    /// Right multiply `matrix` by a 2x2 block acting on the levels `pair`.
+    fn apply_cols(matrix: &mut Array2<c64>, pair: (usize, usize), block: ArrayView2<c64>) {
+        let (left, right) = (
+            matrix.column(pair.0).to_owned(),
+            matrix.column(pair.1).to_owned(),
+        );
+        matrix
+            .column_mut(pair.0)
+            .assign(&(&left * block[[0, 0]] + &right * block[[1, 0]]));
+        matrix
+            .column_mut(pair.1)
+            .assign(&(&left * block[[0, 1]] + &right * block[[1, 1]]));
+    }
+
+    /// Decompose a matrix in SU(d) into the gate's parameters.
+    ///
+    /// Right multiplying by inverse Givens rotations clears each row below the
+    /// diagonal from the bottom up, leaving the diagonal `D`.
+    fn decompose(&self, mut utry: Array2<c64>) -> Vec<f64> {
+        let mut givens = Vec::with_capacity(2 * self.pairs.len());
+        for &(col, row) in &self.pairs {
+            let (a, b) = (utry[[row, col]], utry[[row, row]]);
+            let params = [2. * a.norm().atan2(b.norm()), b.arg() - a.arg()];
+            let inverse = Self::givens(&params).t().conj();
+            Self::apply_cols(&mut utry, (col, row), inverse.view());
+            givens.extend(params);
+        }
+        let mut phases: Vec<f64> = utry.diag().iter().map(|x| x.arg()).collect();
+        let excess = (phases.iter().sum::<f64>() / (2. * std::f64::consts::PI)).round();
+        phases[0] -= excess * 2. * std::f64::consts::PI;
+        let mut params: Vec<f64> = phases[1..].iter().map(|phase| 2. * phase).collect();
+        params.extend(givens);
+        params
+    }
+}
+
+impl Unitary for SpecialUnitaryGate {
+    fn num_params(&self) -> usize {
+        self.radix * self.radix - 1
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let offset = self.radix - 1;
+        let mut unitary = Array2::eye(self.radix);
+        for (k, &pair) in self.pairs.iter().enumerate() {
+            let block = Self::givens(&params[offset + 2 * k..offset + 2 * k + 2]);
+            Self::apply_rows(&mut unitary, pair, block.view());
+        }
+        for (mut row, phase) in unitary.outer_iter_mut().zip(self.phases(params)) {
+            row *= i!(phase).exp();
+        }
+        unitary
+    }
+}
+
+impl Gradient for SpecialUnitaryGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        _const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let offset = self.radix - 1;
+        let blocks: Vec<Array2<c64>> = (0..self.pairs.len())
+            .map(|k| Self::givens(&params[offset + 2 * k..offset + 2 * k + 2]))
+            .collect();
+
+        // prefixes[k] = G_k ... G_1
+        let mut prefixes = vec![Array2::eye(self.radix)];
+        for (block, &pair) in blocks.iter().zip(&self.pairs) {
+            let mut next = prefixes.last().unwrap().clone();
+            Self::apply_rows(&mut next, pair, block.view());
+            prefixes.push(next);
+        }
+
+        let phases = self.phases(params);
+        let diag = Array2::from_diag(&Array1::from_iter(phases.iter().map(|&p| i!(p).exp())));
+        let mut suffix = diag.clone();
+        let mut grad = Array3::zeros((self.num_params(), self.radix, self.radix));
+        for k in (0..self.pairs.len()).rev() {
+            let pair = self.pairs[k];
+            let cols = suffix.select(Axis(1), &[pair.0, pair.1]);
+            let rows = prefixes[k].select(Axis(0), &[pair.0, pair.1]);
+            let jac = Self::givens_jac(&params[offset + 2 * k..offset + 2 * k + 2]);
+            for (j, d_block) in jac.iter().enumerate() {
+                grad.slice_mut(s![offset + 2 * k + j, .., ..])
+                    .assign(&cols.matmul(d_block.view()).matmul(rows.view()));
+            }
+            Self::apply_cols(&mut suffix, pair, blocks[k].view());
+        }
+
+        let rotations = prefixes.pop().unwrap();
+        let unitary = diag.matmul(rotations.view());
+        for k in 0..offset {
+            let mut d_utry = grad.slice_mut(s![k, .., ..]);
+            d_utry.row_mut(0).assign(&(&unitary.row(0) * i!(-0.5)));
+            d_utry
+                .row_mut(k + 1)
+                .assign(&(&unitary.row(k + 1) * i!(0.5)));
+        }
+        (unitary, grad)
+    }
+}
+
+impl Hessian for SpecialUnitaryGate {}
+
+impl Size for SpecialUnitaryGate {
+    fn num_qudits(&self) -> usize {
+        1
+    }
+}
+
+impl Optimize for SpecialUnitaryGate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let utry = optimal_unitary(env_matrix);
+        let det = utry.det().unwrap();
+        let special = utry * i!(-det.arg() / self.radix as f64).exp();
+        self.decompose(special)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+
+    #[test]
+    fn optimize_reaches_nuclear_norm() {
+        let gate = SpecialUnitaryGate::new(3);
+        let mut rng = rng(12);
+        for _ in 0..20 {
+            let mut env = random_env(3, &mut rng);
+            let params = gate.optimize(env.view_mut());
+            let utry = gate.get_utry(&params, &[]);
+            let reached = trace_with(env.view(), utry.view()).norm();
+            assert!((reached - nuclear_norm(env.view())).abs() < 1e-10);
+        }
+    }
+}
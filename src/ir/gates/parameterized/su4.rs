@@ -0,0 +1,278 @@
+use crate::ir::gates::utils::{optimal_unitary, rot_y, rot_y_jac, rot_z, rot_z_jac};
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::squaremat::*;
+use crate::{i, r};
+
+use ndarray::{s, Array1, Array2, Array3, ArrayView2, ArrayViewMut2};
+use ndarray_linalg::{c64, Determinant, Eigh, UPLO};
+
+/// Signs of XX, YY and ZZ on the magic basis vectors, in which all three are diagonal.
+const MAGIC_XX: [f64; 4] = [1., 1., -1., -1.];
+const MAGIC_YY: [f64; 4] = [-1., 1., -1., 1.];
+const MAGIC_ZZ: [f64; 4] = [1., -1., -1., 1.];
+
+/// A general two-qubit gate in SU(4), using the KAK parameterization
+/// `(K1 ⊗ K2) exp(i(a XX + b YY + c ZZ)) (K3 ⊗ K4)`.
+///
+/// The parameters are `[a, b, c]` followed by the ZYZ Euler angles of `K1`, `K2`, `K3`, `K4`.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct SU4Gate();
+
+impl SU4Gate {
+    pub fn new() -> Self {
+        SU4Gate {}
+    }
+}
+
+/// Calculate `RZ(α) RY(β) RZ(γ)`.
+fn su2(params: &[f64]) -> Array2<c64> {
+    rot_z(params[0], None)
+        .matmul(rot_y(params[1]).view())
+        .matmul(rot_z(params[2], None).view())
+}
+
+fn su2_jac(params: &[f64]) -> [Array2<c64>; 3] {
+    let (z1, y, z2) = (
+        rot_z(params[0], None),
+        rot_y(params[1]),
+        rot_z(params[2], None),
+    );
+    let (dz1, dy, dz2) = (
+        rot_z_jac(params[0], None),
+        rot_y_jac(params[1]),
+        rot_z_jac(params[2], None),
+    );
+    [
+        dz1.slice(s![0, .., ..]).matmul(y.view()).matmul(z2.view()),
+        z1.matmul(dy.slice(s![0, .., ..])).matmul(z2.view()),
+        z1.matmul(y.view()).matmul(dz2.slice(s![0, .., ..])),
+    ]
+}
+
+/// Recover the ZYZ Euler angles of a matrix in SU(2).
+fn su2_params(utry: ArrayView2<c64>) -> [f64; 3] {
+    let beta = 2. * utry[[1, 0]].norm().atan2(utry[[0, 0]].norm());
+    let (arg00, arg10) = (utry[[0, 0]].arg(), utry[[1, 0]].arg());
+    [arg10 - arg00, beta, -arg00 - arg10]
+}
+
+/// Split a matrix in SU(2) ⊗ SU(2) into its two factors.
+fn split_kron(utry: ArrayView2<c64>) -> (Array2<c64>, Array2<c64>) {
+    let block = |row: usize, col: usize| utry.slice(s![2 * row..2 * row + 2, 2 * col..2 * col + 2]);
+    let (row, col) = [(0, 0), (0, 1), (1, 0), (1, 1)]
+        .into_iter()
+        .max_by(|&(a, b), &(c, d)| {
+            let norm = |m: ArrayView2<c64>| m.iter().map(|x| x.norm_sqr()).sum::<f64>();
+            norm(block(a, b)).total_cmp(&norm(block(c, d)))
+        })
+        .unwrap();
+    let largest = block(row, col);
+    let det = largest[[0, 0]] * largest[[1, 1]] - largest[[0, 1]] * largest[[1, 0]];
+    let second = largest.to_owned() / det.sqrt();
+    let first = Array2::from_shape_fn((2, 2), |(r, c)| {
+        (&second.mapv(|x| x.conj()) * &block(r, c)).sum() / 2.
+    });
+    (first, second)
+}
+
+/// Calculate `exp(i(a XX + b YY + c ZZ))` or one of its derivatives.
+///
+/// The matrix acts as `p1 I + q1 X` on span{|00>, |11>} and `p2 I + q2 X` on span{|01>, |10>}.
+fn core_matrix(p1: c64, q1: c64, p2: c64, q2: c64) -> Array2<c64> {
+    let zero = r!(0.0);
+    Array2::from_shape_vec(
+        (4, 4),
+        vec![
+            p1, zero, zero, q1, zero, p2, q2, zero, zero, q2, p2, zero, q1, zero, zero, p1,
+        ],
+    )
+    .unwrap()
+}
+
+fn core(params: &[f64]) -> Array2<c64> {
+    let (a, b, c) = (params[0], params[1], params[2]);
+    let (pos, neg) = (i!(c).exp(), i!(-c).exp());
+    core_matrix(
+        pos * (a - b).cos(),
+        pos * i!((a - b).sin()),
+        neg * (a + b).cos(),
+        neg * i!((a + b).sin()),
+    )
+}
+
+fn core_jac(params: &[f64]) -> [Array2<c64>; 3] {
+    let (a, b, c) = (params[0], params[1], params[2]);
+    let (pos, neg) = (i!(c).exp(), i!(-c).exp());
+    let (p1, q1) = (pos * (a - b).cos(), pos * i!((a - b).sin()));
+    let (p2, q2) = (neg * (a + b).cos(), neg * i!((a + b).sin()));
+    let (dp1, dq1) = (pos * -(a - b).sin(), pos * i!((a - b).cos()));
+    let (dp2, dq2) = (neg * -(a + b).sin(), neg * i!((a + b).cos()));
+    [
+        core_matrix(dp1, dq1, dp2, dq2),
+        core_matrix(-dp1, -dq1, dp2, dq2),
+        core_matrix(i!(1.) * p1, i!(1.) * q1, i!(-1.) * p2, i!(-1.) * q2),
+    ]
+}
+
+/// The columns are the magic basis states Φ+, iΨ+, Ψ-, iΦ-.
+fn magic_basis() -> Array2<c64> {
+    let h = r!(std::f64::consts::FRAC_1_SQRT_2);
+    let ih = i!(std::f64::consts::FRAC_1_SQRT_2);
+    let zero = r!(0.0);
+    Array2::from_shape_vec(
+        (4, 4),
+        vec![
+            h, zero, zero, ih, zero, ih, h, zero, zero, ih, -h, zero, h, zero, zero, -ih,
+        ],
+    )
+    .unwrap()
+}
+
+/// Decompose a matrix in SU(4) into the gate's parameters.
+fn su4_params(utry: ArrayView2<c64>) -> Vec<f64> {
+    let magic = magic_basis();
+    let magic_h = magic.t().conj();
+    let in_magic = magic_h.matmul(utry).matmul(magic.view());
+    let squared = in_magic.t().matmul(in_magic.view());
+
+    // The real and imaginary parts of the symmetric unitary `squared` commute,
+    // so a generic real combination of them shares their eigenvectors.
+    let mut eigvecs = Array2::<f64>::eye(4);
+    for coef in [0.7548776662, 1.3247179572, 0.5698402910, 2.1478990357] {
+        let combined = squared.mapv(|x| x.re + coef * x.im);
+        let (_, vecs) = combined.eigh(UPLO::Lower).unwrap();
+        eigvecs = vecs;
+        let complex_vecs = eigvecs.mapv(|x| r!(x));
+        let diag = complex_vecs
+            .t()
+            .matmul(squared.view())
+            .matmul(complex_vecs.view());
+        let off_diag: f64 = diag
+            .indexed_iter()
+            .filter(|((r, c), _)| r != c)
+            .map(|(_, x)| x.norm())
+            .sum();
+        if off_diag < 1e-9 {
+            break;
+        }
+    }
+    if eigvecs.det().unwrap() < 0. {
+        eigvecs.column_mut(0).mapv_inplace(|x| -x);
+    }
+    let eigvecs = eigvecs.mapv(|x| r!(x));
+    let eigvals = eigvecs
+        .t()
+        .matmul(squared.view())
+        .matmul(eigvecs.view())
+        .diag()
+        .to_owned();
+
+    // Choose square roots of the eigenvalues whose phases sum to zero
+    let mut phases: Array1<f64> = eigvals.mapv(|x| x.arg() / 2.);
+    let excess = (phases.sum() / std::f64::consts::PI).round();
+    phases[0] -= excess * std::f64::consts::PI;
+
+    let left_magic = in_magic.matmul(eigvecs.view()) * &phases.mapv(|p| i!(-p).exp());
+    let left = magic.matmul(left_magic.view()).matmul(magic_h.view());
+    let right = magic.matmul(eigvecs.t()).matmul(magic_h.view());
+
+    let dot = |signs: [f64; 4]| signs.iter().zip(&phases).map(|(s, p)| s * p).sum::<f64>() / 4.;
+    let mut params = vec![dot(MAGIC_XX), dot(MAGIC_YY), dot(MAGIC_ZZ)];
+    let (k1, k2) = split_kron(left.view());
+    let (k3, k4) = split_kron(right.view());
+    for k in [k1, k2, k3, k4] {
+        params.extend(su2_params(k.view()));
+    }
+    params
+}
+
+impl Unitary for SU4Gate {
+    fn num_params(&self) -> usize {
+        15
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let left = su2(&params[3..6]).kron(&su2(&params[6..9]));
+        let right = su2(&params[9..12]).kron(&su2(&params[12..15]));
+        left.matmul(core(&params[..3]).view()).matmul(right.view())
+    }
+}
+
+impl Gradient for SU4Gate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        _const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let locals: Vec<Array2<c64>> = (0..4).map(|k| su2(&params[3 + 3 * k..6 + 3 * k])).collect();
+        let left = locals[0].kron(&locals[1]);
+        let right = locals[2].kron(&locals[3]);
+        let core_utry = core(&params[..3]);
+        let core_right = core_utry.matmul(right.view());
+        let left_core = left.matmul(core_utry.view());
+
+        let mut grad = Array3::zeros((15, 4, 4));
+        for (k, d_core) in core_jac(&params[..3]).iter().enumerate() {
+            grad.slice_mut(s![k, .., ..])
+                .assign(&left.matmul(d_core.view()).matmul(right.view()));
+        }
+        for (k, d_local) in su2_jac(&params[3..6]).iter().enumerate() {
+            grad.slice_mut(s![3 + k, .., ..])
+                .assign(&d_local.kron(&locals[1]).matmul(core_right.view()));
+        }
+        for (k, d_local) in su2_jac(&params[6..9]).iter().enumerate() {
+            grad.slice_mut(s![6 + k, .., ..])
+                .assign(&locals[0].kron(d_local).matmul(core_right.view()));
+        }
+        for (k, d_local) in su2_jac(&params[9..12]).iter().enumerate() {
+            grad.slice_mut(s![9 + k, .., ..])
+                .assign(&left_core.matmul(d_local.kron(&locals[3]).view()));
+        }
+        for (k, d_local) in su2_jac(&params[12..15]).iter().enumerate() {
+            grad.slice_mut(s![12 + k, .., ..])
+                .assign(&left_core.matmul(locals[2].kron(d_local).view()));
+        }
+        (left_core.matmul(right.view()), grad)
+    }
+}
+
+impl Hessian for SU4Gate {}
+
+impl Size for SU4Gate {
+    fn num_qudits(&self) -> usize {
+        2
+    }
+}
+
+impl Optimize for SU4Gate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let utry = optimal_unitary(env_matrix);
+        let det = utry.det().unwrap();
+        let special = utry * i!(-det.arg() / 4.).exp();
+        su4_params(special.view())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+
+    #[test]
+    fn optimize_reaches_nuclear_norm() {
+        let gate = SU4Gate::new();
+        let mut rng = rng(11);
+        for _ in 0..20 {
+            let mut env = random_env(4, &mut rng);
+            let params = gate.optimize(env.view_mut());
+            let utry = gate.get_utry(&params, &[]);
+            let reached = trace_with(env.view(), utry.view()).norm();
+            assert!((reached - nuclear_norm(env.view())).abs() < 1e-10);
+        }
+    }
+}
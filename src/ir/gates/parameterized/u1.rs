@@ -1,11 +1,11 @@
 use std::f64::consts::PI;
 
 use crate::ir::gates::utils::{rot_z, rot_z_jac};
-use crate::ir::gates::{Gradient, Size};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
 use crate::i;
 
-use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2};
 use ndarray_linalg::c64;
 
 /// IBM's U1 single qubit gate
@@ -50,6 +50,14 @@ impl Gradient for U1Gate {
     }
 }
 
+impl Hessian for U1Gate {
+    fn get_hessian(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array4<c64> {
+        let mut hess = Array4::zeros((1, 1, 2, 2));
+        hess[[0, 0, 1, 1]] = -i!(params[0]).exp();
+        hess
+    }
+}
+
 impl Size for U1Gate {
     fn num_qudits(&self) -> usize {
         1
@@ -1,4 +1,7 @@
+use std::f64::consts::PI;
+
 use crate::ir::gates::Gradient;
+use crate::ir::gates::Hessian;
 use crate::ir::gates::Optimize;
 use crate::ir::gates::Size;
 use crate::ir::gates::Unitary;
@@ -6,6 +9,7 @@ use crate::{i, r};
 
 use ndarray::Array2;
 use ndarray::Array3;
+use ndarray::ArrayViewMut2;
 use ndarray_linalg::c64;
 
 /// IBM's U2 single qubit gate
@@ -88,10 +92,78 @@ impl Gradient for U2Gate {
     }
 }
 
+impl Hessian for U2Gate {}
+
 impl Size for U2Gate {
     fn num_qudits(&self) -> usize {
         1
     }
 }
 
-impl Optimize for U2Gate {}
+impl Optimize for U2Gate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        // sqrt(2) Tr(env @ U2) = (e00 + e01 e^(i phi)) + e^(i lambda) (e11 e^(i phi) - e10),
+        // so for a fixed phi the best lambda aligns the two terms, leaving a 1-d problem in phi.
+        let terms = |phi: f64| {
+            let rot = (i!(1.0) * phi).exp();
+            (
+                env_matrix[[0, 0]] + env_matrix[[0, 1]] * rot,
+                env_matrix[[1, 1]] * rot - env_matrix[[1, 0]],
+            )
+        };
+        let objective = |phi: f64| {
+            let (a, b) = terms(phi);
+            a.norm() + b.norm()
+        };
+
+        // The objective is the sum of two single-peaked periodic terms, so a coarse scan
+        // brackets the global maximum and a golden-section search refines it.
+        let steps = 32;
+        let width = 2.0 * PI / steps as f64;
+        let start = (0..steps)
+            .map(|k| k as f64 * width)
+            .fold((0.0, f64::NEG_INFINITY), |best, phi| {
+                let value = objective(phi);
+                if value > best.1 {
+                    (phi, value)
+                } else {
+                    best
+                }
+            })
+            .0;
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut lo, mut hi) = (start - width, start + width);
+        while hi - lo > 1e-12 {
+            let left = hi - ratio * (hi - lo);
+            let right = lo + ratio * (hi - lo);
+            if objective(left) < objective(right) {
+                lo = left;
+            } else {
+                hi = right;
+            }
+        }
+        let phi = (lo + hi) / 2.0;
+        let (a, b) = terms(phi);
+        vec![phi, a.arg() - b.arg()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+
+    #[test]
+    fn optimize_reaches_numeric_maximum() {
+        let gate = U2Gate::new();
+        let mut rng = rng(2);
+        for _ in 0..10 {
+            let mut env = random_env(2, &mut rng);
+            let params = gate.optimize(env.view_mut());
+            let objective =
+                |params: &[f64]| trace_with(env.view(), gate.get_utry(params, &[]).view()).norm();
+            let best = numeric_max(objective, 2, &mut rng);
+            assert!(objective(&params) > best - 1e-10);
+        }
+    }
+}
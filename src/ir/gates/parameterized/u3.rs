@@ -1,4 +1,8 @@
+use std::f64::consts::PI;
+
+use crate::ir::gates::utils::optimal_unitary;
 use crate::ir::gates::Gradient;
+use crate::ir::gates::Hessian;
 use crate::ir::gates::Optimize;
 use crate::ir::gates::Size;
 use crate::ir::gates::Unitary;
@@ -6,6 +10,8 @@ use crate::{i, r};
 
 use ndarray::Array2;
 use ndarray::Array3;
+use ndarray::ArrayView2;
+use ndarray::ArrayViewMut2;
 use ndarray_linalg::c64;
 
 /// IBM's U3 single qubit gate
@@ -16,6 +22,76 @@ impl U3Gate {
     pub fn new() -> Self {
         U3Gate {}
     }
+
+    /// Find the parameters that maximize `|offset + Tr(env_matrix @ U)|`.
+    ///
+    /// This is the update for a U3 block of a larger gate whose other blocks are
+    /// fixed and add `offset` to the trace, where the phase-free update is not
+    /// enough. Writing U3 as `[[u, -e^(ib) v*], [v, e^(ib) u]]` with `u` real and
+    /// `u^2 + |v|^2 = 1`, the real part of `e^(-ia) Tr(env_matrix @ U)` is linear
+    /// in `(u, Re v, Im v)` for fixed `b`, so its maximum over the unit sphere is
+    /// the norm of the coefficients. That leaves a smooth function of the two
+    /// phases `a` and `b`, which is searched on a grid and refined by pattern
+    /// search.
+    pub fn optimize_with_offset(&self, env_matrix: ArrayView2<c64>, offset: c64) -> Vec<f64> {
+        let coefficients = |a: f64, b: f64| {
+            let rotation = i!(-a).exp();
+            let phase = i!(b).exp();
+            let e = &env_matrix;
+            [
+                (rotation * (e[[0, 0]] + e[[1, 1]] * phase)).re,
+                (rotation * (e[[0, 1]] - e[[1, 0]] * phase)).re,
+                (rotation * i!(1.0) * (e[[0, 1]] + e[[1, 0]] * phase)).re,
+            ]
+        };
+        let objective = |a: f64, b: f64| {
+            let w = coefficients(a, b);
+            (i!(-a).exp() * offset).re + (w[0] * w[0] + w[1] * w[1] + w[2] * w[2]).sqrt()
+        };
+
+        const GRID: usize = 16;
+        let spacing = 2.0 * PI / GRID as f64;
+        let mut starts: Vec<(f64, f64, f64)> = (0..GRID * GRID)
+            .map(|k| {
+                let (a, b) = ((k / GRID) as f64 * spacing, (k % GRID) as f64 * spacing);
+                (objective(a, b), a, b)
+            })
+            .collect();
+        starts.sort_by(|x, y| y.0.partial_cmp(&x.0).unwrap());
+
+        let mut best = starts[0];
+        for &(mut value, mut a, mut b) in starts.iter().take(4) {
+            let mut step = spacing / 2.0;
+            while step > 1e-12 {
+                let mut improved = false;
+                for (da, db) in [(step, 0.0), (-step, 0.0), (0.0, step), (0.0, -step)] {
+                    let trial = objective(a + da, b + db);
+                    if trial > value {
+                        value = trial;
+                        a += da;
+                        b += db;
+                        improved = true;
+                    }
+                }
+                if !improved {
+                    step /= 2.0;
+                }
+            }
+            if value > best.0 {
+                best = (value, a, b);
+            }
+        }
+
+        let (_, a, b) = best;
+        let w = coefficients(a, b);
+        let norm = (w[0] * w[0] + w[1] * w[1] + w[2] * w[2]).sqrt();
+        let (u, v) = if norm > 0.0 {
+            (w[0] / norm, c64::new(w[1], w[2]) / norm)
+        } else {
+            (1.0, c64::new(0.0, 0.0))
+        };
+        vec![2.0 * v.norm().atan2(u), v.arg(), b - v.arg()]
+    }
 }
 
 impl Unitary for U3Gate {
@@ -123,10 +199,59 @@ impl Gradient for U3Gate {
     }
 }
 
+impl Hessian for U3Gate {}
+
 impl Size for U3Gate {
     fn num_qudits(&self) -> usize {
         1
     }
 }
 
-impl Optimize for U3Gate {}
+impl Optimize for U3Gate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let utry = optimal_unitary(env_matrix);
+        u3_params(utry.view())
+    }
+}
+
+/// Recover the U3 parameters of a 2x2 unitary, discarding its global phase.
+///
+/// The phases are read from whichever of the diagonal or off-diagonal entries are
+/// larger, which keeps the extraction stable near `theta = 0` and `theta = pi`.
+fn u3_params(utry: ArrayView2<c64>) -> Vec<f64> {
+    let cos = utry[[0, 0]].norm();
+    let sin = utry[[1, 0]].norm();
+    let theta = 2.0 * sin.atan2(cos);
+    if cos >= sin {
+        // utry[0, 0] = e^(i a) cos, utry[1, 1] = e^(i (a + phi + lambda)) cos
+        let phase = utry[[0, 0]].arg();
+        let phi = utry[[1, 0]].arg() - phase;
+        let lambda = utry[[1, 1]].arg() - phase - phi;
+        vec![theta, phi, lambda]
+    } else {
+        // utry[1, 0] = e^(i (a + phi)) sin, -utry[0, 1] = e^(i (a + lambda)) sin
+        let phase = utry[[1, 0]].arg() + (-utry[[0, 1]]).arg() - utry[[1, 1]].arg();
+        let phi = utry[[1, 0]].arg() - phase;
+        let lambda = (-utry[[0, 1]]).arg() - phase;
+        vec![theta, phi, lambda]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+
+    #[test]
+    fn optimize_reaches_nuclear_norm() {
+        let gate = U3Gate::new();
+        let mut rng = rng(2);
+        for _ in 0..20 {
+            let mut env = random_env(2, &mut rng);
+            let params = gate.optimize(env.view_mut());
+            let utry = gate.get_utry(&params, &[]);
+            let reached = trace_with(env.view(), utry.view()).norm();
+            assert!((reached - nuclear_norm(env.view())).abs() < 1e-10);
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use crate::ir::gates::utils::optimal_unitary;
 use crate::ir::gates::Gradient;
+use crate::ir::gates::Hessian;
 use crate::ir::gates::Optimize;
 use crate::ir::gates::Size;
 use crate::ir::gates::Unitary;
@@ -6,6 +8,7 @@ use crate::{i, r};
 
 use ndarray::Array2;
 use ndarray::Array3;
+use ndarray::ArrayViewMut2;
 use ndarray_linalg::c64;
 
 /// IBM's U3 single qubit gate
@@ -170,136 +173,67 @@ impl Gradient for U8Gate {
     fn get_utry_and_grad(
         &self,
         params: &[f64],
-        _const_gates: &[Array2<c64>],
+        const_gates: &[Array2<c64>],
     ) -> (Array2<c64>, Array3<c64>) {
-        let s1 = (params[0]).sin();
-        let c1 = (params[0]).cos();
-        let s2 = (params[1]).sin();
-        let c2 = (params[1]).cos();
-        let s3 = (params[2]).sin();
-        let c3 = (params[2]).cos();
-
-        let p1 = (i!(1.0) * params[3]).exp();
-        let m1 = (i!(-1.0) * params[3]).exp();
-        let p2 = (i!(1.0) * params[4]).exp();
-        let m2 = (i!(-1.0) * params[4]).exp();
-        let p3 = (i!(1.0) * params[5]).exp();
-        let m3 = (i!(-1.0) * params[5]).exp();
-        let p4 = (i!(1.0) * params[6]).exp();
-        let m4 = (i!(-1.0) * params[6]).exp();
-        let p5 = (i!(1.0) * params[7]).exp();
-        let m5 = (i!(-1.0) * params[7]).exp();
-
         (
-            Array2::from_shape_vec(
-                (3, 3),
-                vec![
-                    c1 * c2 * p1,
-                    s1 * p3,
-                    c1 * s2 * p4,
-                    s2 * s3 * m4 * m5 - s1 * c2 * c3 * p1 * p2 * m3,
-                    c1 * c3 * p2,
-                    -c2 * s3 * m1 * m5 - s1 * s2 * c3 * p2 * m3 * p4,
-                    -s1 * c2 * s3 * p1 * m3 * p5 - s2 * c3 * m2 * m4,
-                    c1 * s3 * p5,
-                    c2 * c3 * m1 * m2 - s1 * s2 * s3 * m3 * p4 * p5,
-                ],
-            )
-            .unwrap(),
-            Array3::from_shape_vec(
-                (8, 3, 3),
-                vec![
-                    // param 0
-                    -s1 * c2 * p1,
-                    c1 * p3,
-                    -s1 * s2 * p4,
-                    -c1 * c2 * c3 * p1 * p2 * m3,
-                    -s1 * c3 * p2,
-                    -c1 * s2 * c3 * p2 * m3 * p4,
-                    -c1 * c2 * s3 * p1 * m3 * p5,
-                    -s1 * s3 * p5,
-                    -c1 * s2 * s3 * m3 * p4 * p5,
-                    // param 1
-                    -c1 * s2 * p1,
-                    r!(0.0),
-                    c1 * c2 * p4,
-                    c2 * s3 * m4 * m5 + s1 * s2 * c3 * p1 * p2 * m3,
-                    r!(0.0),
-                    s2 * s3 * m1 * m5 - s1 * c2 * c3 * p2 * m3 * p4,
-                    s1 * s2 * s3 * p1 * m3 * p5 - c2 * c3 * m2 * m4,
-                    r!(0.0),
-                    -s2 * c3 * m1 * m2 - s1 * c2 * s3 * m3 * p4 * p5,
-                    // param 2
-                    r!(0.0),
-                    r!(0.0),
-                    r!(0.0),
-                    s2 * c3 * m4 * m5 + s1 * c2 * s3 * p1 * p2 * m3,
-                    -c1 * s3 * p2,
-                    -c2 * c3 * m1 * m5 + s1 * s2 * s3 * p2 * m3 * p4,
-                    -s1 * c2 * c3 * p1 * m3 * p5 + s2 * s3 * m2 * m4,
-                    c1 * c3 * p5,
-                    -c2 * s3 * m1 * m2 - s1 * s2 * c3 * m3 * p4 * p5,
-                    // param 3
-                    i!(1.0) * c1 * c2 * p1,
-                    r!(0.0),
-                    r!(0.0),
-                    -i!(1.0) * s1 * c2 * c3 * p1 * p2 * m3,
-                    r!(0.0),
-                    i!(1.0) * c2 * s3 * m1 * m5,
-                    -i!(1.0) * s1 * c2 * s3 * p1 * m3 * p5,
-                    r!(0.0),
-                    -i!(1.0) * c2 * c3 * m1 * m2,
-                    // param 4
-                    r!(0.0),
-                    r!(0.0),
-                    r!(0.0),
-                    -i!(1.0) * s1 * c2 * c3 * p1 * p2 * m3,
-                    i!(1.0) * c1 * c3 * p2,
-                    -i!(1.0) * s1 * s2 * c3 * p2 * m3 * p4,
-                    i!(1.0) * s2 * c3 * m2 * m4,
-                    r!(0.0),
-                    -i!(1.0) * c2 * c3 * m1 * m2,
-                    // param 5
-                    r!(0.0),
-                    i!(1.0) * s1 * p3,
-                    r!(0.0),
-                    i!(1.0) * s1 * c2 * c3 * p1 * p2 * m3,
-                    r!(0.0),
-                    i!(1.0) * s1 * s2 * c3 * p2 * m3 * p4,
-                    i!(1.0) * s1 * c2 * s3 * p1 * m3 * p5,
-                    r!(0.0),
-                    i!(1.0) * s1 * s2 * s3 * m3 * p4 * p5,
-                    // param 6
-                    r!(0.0),
-                    r!(0.0),
-                    i!(1.0) * c1 * s2 * p4,
-                    -i!(1.0) * s2 * s3 * m4 * m5,
-                    r!(0.0),
-                    -i!(1.0) * s1 * s2 * c3 * p2 * m3 * p4,
-                    i!(1.0) * s2 * c3 * m2 * m4,
-                    r!(0.0),
-                    -i!(1.0) * s1 * s2 * s3 * m3 * p4 * p5,
-                    // param 7
-                    r!(0.0),
-                    r!(0.0),
-                    r!(0.0),
-                    -i!(1.0) * s2 * s3 * m4 * m5,
-                    r!(0.0),
-                    i!(1.0) * c2 * s3 * m1 * m5,
-                    -i!(1.0) * s1 * c2 * s3 * p1 * m3 * p5,
-                    i!(1.0) * c1 * s3 * p5,
-                    -i!(1.0) * s1 * s2 * s3 * m3 * p4 * p5,
-                ],
-            )
-            .unwrap(),
+            self.get_utry(params, const_gates),
+            self.get_grad(params, const_gates),
         )
     }
 }
 
+impl Hessian for U8Gate {}
+
 impl Size for U8Gate {
     fn num_qudits(&self) -> usize {
         1
     }
 }
 
-impl Optimize for U8Gate {}
+impl Optimize for U8Gate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let utry = optimal_unitary(env_matrix);
+
+        // Strip the global phase so that the optimal unitary lies in SU(3)
+        let det = utry[[0, 0]] * (utry[[1, 1]] * utry[[2, 2]] - utry[[1, 2]] * utry[[2, 1]])
+            - utry[[0, 1]] * (utry[[1, 0]] * utry[[2, 2]] - utry[[1, 2]] * utry[[2, 0]])
+            + utry[[0, 2]] * (utry[[1, 0]] * utry[[2, 1]] - utry[[1, 1]] * utry[[2, 0]]);
+        let su = utry * (i!(-1.0) * det.arg() / 3.0).exp();
+
+        // The first row and middle column fix every angle and phase of the parameterization
+        let theta1 = su[[0, 1]]
+            .norm()
+            .atan2((su[[0, 0]].norm_sqr() + su[[0, 2]].norm_sqr()).sqrt());
+        let theta2 = su[[0, 2]].norm().atan2(su[[0, 0]].norm());
+        let theta3 = su[[2, 1]].norm().atan2(su[[1, 1]].norm());
+        vec![
+            theta1,
+            theta2,
+            theta3,
+            su[[0, 0]].arg(),
+            su[[1, 1]].arg(),
+            su[[0, 1]].arg(),
+            su[[0, 2]].arg(),
+            su[[2, 1]].arg(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+
+    #[test]
+    fn optimize_reaches_nuclear_norm() {
+        let gate = U8Gate::new();
+        let mut rng = rng(3);
+        for _ in 0..20 {
+            let mut env = random_env(3, &mut rng);
+            let params = gate.optimize(env.view_mut());
+            let utry = gate.get_utry(&params, &[]);
+            let reached = trace_with(env.view(), utry.view()).norm();
+            assert!((reached - nuclear_norm(env.view())).abs() < 1e-10);
+        }
+    }
+}
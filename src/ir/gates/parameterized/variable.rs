@@ -1,29 +1,11 @@
-use crate::ir::gates::{Gradient, Size};
+use crate::i;
+use crate::ir::gates::utils::{optimal_unitary, svd};
+use crate::ir::gates::{Gradient, Hessian, Size};
 use crate::ir::gates::{Optimize, Unitary};
-
-use ndarray::{Array2, Array3, ArrayViewMut2};
-use ndarray_linalg::c64;
 use crate::squaremat::*;
 
-// use lax::{layout::MatrixLayout, UVTFlag, SVDDC_};
-use ndarray_linalg::SVD;
-
-fn svd(matrix: ArrayViewMut2<c64>) -> (Array2<c64>, Array2<c64>) {
-    // let size = matrix.shape()[0];
-    // let layout = MatrixLayout::C {
-    //     row: size as i32,
-    //     lda: size as i32,
-    // };
-    let result = matrix.svd(true, true).unwrap();
-    // Safety: u/vt are the same size since matrix is a square matrix with sides of size `size`
-    (result.0.unwrap(), result.2.unwrap())
-    // unsafe {
-    //     (
-    //         Array2::from_shape_vec_unchecked((size, size), result.U.unwrap()),
-    //         Array2::from_shape_vec_unchecked((size, size), result.VT.unwrap()),
-    //     )
-    // }
-}
+use ndarray::{s, Array2, Array3, ArrayViewMut2};
+use ndarray_linalg::{c64, SVD};
 
 /// A variable n-qudit unitary gate
 #[derive(Clone, Debug, PartialEq, Default)]
@@ -46,14 +28,9 @@ impl VariableUnitaryGate {
             num_parameters: 2 * dim.pow(2u32),
         }
     }
-}
-
-impl Unitary for VariableUnitaryGate {
-    fn num_params(&self) -> usize {
-        self.num_parameters
-    }
 
-    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+    /// Build the (generally non-unitary) matrix described by `params`.
+    fn get_matrix(&self, params: &[f64]) -> Array2<c64> {
         assert_eq!(self.num_params(), params.len());
         let mid = params.len() / 2;
         let (re, im) = params.split_at(mid);
@@ -63,27 +40,75 @@ impl Unitary for VariableUnitaryGate {
             .map(|(re, im)| c64::new(*re, *im))
             .collect();
         let len = vec.len();
-        let mut matrix = Array2::from_shape_vec((self.dim, self.dim), vec)
-            .unwrap_or_else(|_| panic!("Got vec of length {}, self.dim is {}", len, self.dim));
+        Array2::from_shape_vec((self.dim, self.dim), vec)
+            .unwrap_or_else(|_| panic!("Got vec of length {}, self.dim is {}", len, self.dim))
+    }
+}
+
+impl Unitary for VariableUnitaryGate {
+    fn num_params(&self) -> usize {
+        self.num_parameters
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let mut matrix = self.get_matrix(params);
         let (u, vt) = svd(matrix.view_mut());
         u.matmul(vt.view())
     }
 }
 
 impl Gradient for VariableUnitaryGate {
-    fn get_grad(&self, _params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
-        unimplemented!()
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
     }
 
+    /// The unitary is the polar factor `W V^†` of the parameter matrix `M = W S V^†`.
+    /// Its differential is `W Ω V^†` with `Ω_ij = (C_ij - C_ji^*) / (s_i + s_j)`,
+    /// where `C = W^† dM V`. When `M` is rank-deficient, the polar factor is not
+    /// unique on its null space, and the terms with `s_i + s_j = 0` are taken as 0.
     fn get_utry_and_grad(
         &self,
-        _params: &[f64],
+        params: &[f64],
         _const_gates: &[Array2<c64>],
     ) -> (Array2<c64>, Array3<c64>) {
-        unimplemented!()
+        let matrix = self.get_matrix(params);
+        let (w, sigma, vt) = matrix.svd(true, true).unwrap();
+        let (w, vt) = (w.unwrap(), vt.unwrap());
+        let utry = w.matmul(vt.view());
+        let v = vt.t().conj();
+        let dim = self.dim;
+        let mid = self.num_parameters / 2;
+        let cutoff = sigma[0] * dim as f64 * f64::EPSILON;
+        let inv_sum = |i: usize, j: usize| {
+            let sum = sigma[i] + sigma[j];
+            if sum <= cutoff {
+                0.0
+            } else {
+                1.0 / sum
+            }
+        };
+        let mut grad = Array3::zeros((self.num_parameters, dim, dim));
+        for a in 0..dim {
+            for b in 0..dim {
+                let c = Array2::from_shape_fn((dim, dim), |(i, j)| w[[a, i]].conj() * v[[b, j]]);
+                let re_omega = Array2::from_shape_fn((dim, dim), |(i, j)| {
+                    (c[[i, j]] - c[[j, i]].conj()) * inv_sum(i, j)
+                });
+                let im_omega = Array2::from_shape_fn((dim, dim), |(i, j)| {
+                    i!(1.0) * (c[[i, j]] + c[[j, i]].conj()) * inv_sum(i, j)
+                });
+                grad.slice_mut(s![a * dim + b, .., ..])
+                    .assign(&w.matmul(re_omega.view()).matmul(vt.view()));
+                grad.slice_mut(s![mid + a * dim + b, .., ..])
+                    .assign(&w.matmul(im_omega.view()).matmul(vt.view()));
+            }
+        }
+        (utry, grad)
     }
 }
 
+impl Hessian for VariableUnitaryGate {}
+
 impl Size for VariableUnitaryGate {
     fn num_qudits(&self) -> usize {
         self.size
@@ -91,11 +116,8 @@ impl Size for VariableUnitaryGate {
 }
 
 impl Optimize for VariableUnitaryGate {
-    fn optimize(&self, mut env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
-        let (mut u, mut vt) = svd(env_matrix.view_mut());
-        u.map_inplace(|i| *i = i.conj());
-        vt.map_inplace(|i| *i = i.conj());
-        let mat = vt.t().matmul(u.t());
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let mat = optimal_unitary(env_matrix);
         let mut ret = vec![0.0; self.num_parameters];
         for (i, cmplx) in mat.iter().enumerate() {
             ret[i % (self.num_parameters / 2)] = cmplx.re;
@@ -104,3 +126,35 @@ impl Optimize for VariableUnitaryGate {
         ret
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::check_gradient;
+    use crate::ir::gates::test_utils::*;
+
+    #[test]
+    fn gradient_matches_finite_differences() {
+        let gate = VariableUnitaryGate::new(2, vec![3, 2]);
+        let mut rng = rng(3);
+        let params = random_params(gate.num_params(), &mut rng);
+        assert!(check_gradient(&gate, &params, &[]).passed(1e-6));
+    }
+
+    #[test]
+    fn gradient_is_finite_when_rank_deficient() {
+        // M = x y^T has rank one, so two of its singular values are zero
+        let gate = VariableUnitaryGate::new(1, vec![3]);
+        let mut rng = rng(4);
+        let (x, y) = (random_params(3, &mut rng), random_params(3, &mut rng));
+        let mut params = vec![0.0; gate.num_params()];
+        for a in 0..3 {
+            for b in 0..3 {
+                params[a * 3 + b] = x[a] * y[b];
+            }
+        }
+        let (utry, grad) = gate.get_utry_and_grad(&params, &[]);
+        assert!(utry.iter().chain(grad.iter()).all(|x| x.is_finite()));
+        assert!(crate::ir::gates::unitarity_error(utry.view()) < 1e-12);
+    }
+}
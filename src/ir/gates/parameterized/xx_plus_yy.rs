@@ -0,0 +1,124 @@
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::{i, r};
+
+use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// The XX+YY interaction `exp(-iθ(XX + YY) / 4)`, with the phase `β` on the
+/// coupling between |01> and |10>
+///
+/// This is the iSWAP family: `θ = -π` gives iSWAP and `θ = -π / 2` gives √iSWAP.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct XXPlusYYGate();
+
+impl XXPlusYYGate {
+    pub fn new() -> Self {
+        XXPlusYYGate {}
+    }
+}
+
+impl Unitary for XXPlusYYGate {
+    fn num_params(&self) -> usize {
+        2
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let cos = r!((params[0] / 2.).cos());
+        let sin = (params[0] / 2.).sin();
+        let upper = i!(-sin) * i!(-params[1]).exp();
+        let lower = i!(-sin) * i!(params[1]).exp();
+        let zero = r!(0.0);
+        let one = r!(1.0);
+        Array2::from_shape_vec(
+            (4, 4),
+            vec![
+                one, zero, zero, zero, zero, cos, upper, zero, zero, lower, cos, zero, zero, zero,
+                zero, one,
+            ],
+        )
+        .unwrap()
+    }
+}
+
+impl Gradient for XXPlusYYGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let utry = self.get_utry(params, const_gates);
+        let dcos = r!(-(params[0] / 2.).sin() / 2.);
+        let dsin = (params[0] / 2.).cos() / 2.;
+        let dupper = i!(-dsin) * i!(-params[1]).exp();
+        let dlower = i!(-dsin) * i!(params[1]).exp();
+        let dphase_upper = i!(-1.) * utry[[1, 2]];
+        let dphase_lower = i!(1.) * utry[[2, 1]];
+        let zero = r!(0.0);
+        (
+            utry,
+            Array3::from_shape_vec(
+                (2, 4, 4),
+                vec![
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    dcos,
+                    dupper,
+                    zero,
+                    zero,
+                    dlower,
+                    dcos,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    dphase_upper,
+                    zero,
+                    zero,
+                    dphase_lower,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                    zero,
+                ],
+            )
+            .unwrap(),
+        )
+    }
+}
+
+impl Hessian for XXPlusYYGate {}
+
+impl Size for XXPlusYYGate {
+    fn num_qudits(&self) -> usize {
+        2
+    }
+}
+
+impl Optimize for XXPlusYYGate {
+    /// The off-diagonal contribution is `sin(θ/2) R cos(β - β0)`, so `β = β0`
+    /// and `θ` then balances it against the diagonal contribution.
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        let diag = (env_matrix[[1, 1]] + env_matrix[[2, 2]]).re;
+        let cos_part = (env_matrix[[2, 1]] + env_matrix[[1, 2]]).im;
+        let sin_part = (env_matrix[[1, 2]] - env_matrix[[2, 1]]).re;
+        let off_diag = cos_part.hypot(sin_part);
+        vec![2. * off_diag.atan2(diag), sin_part.atan2(cos_part)]
+    }
+}
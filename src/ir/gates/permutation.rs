@@ -0,0 +1,96 @@
+use ndarray::Array2;
+use ndarray::Array3;
+use ndarray::ArrayViewMut2;
+use ndarray_linalg::c64;
+
+use super::Gradient;
+use super::Hessian;
+use super::Optimize;
+use super::Size;
+use super::Unitary;
+use crate::permutation_matrix::Permutation;
+use crate::r;
+
+/// A gate that reorders qudits, moving qudit `i` of its location to position `perm[i]`
+///
+/// `UnitaryBuilder` applies it by relabeling tensor indices, so it is free at evaluation time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermutationGate {
+    perm: Permutation,
+    radixes: Vec<usize>,
+    dim: usize,
+}
+
+impl PermutationGate {
+    pub fn new(perm: Permutation, radixes: Vec<usize>) -> Self {
+        assert_eq!(perm.as_slice().len(), radixes.len());
+        assert!(
+            perm.as_slice()
+                .iter()
+                .enumerate()
+                .all(|(i, &p)| radixes[i] == radixes[p]),
+            "A permutation may only exchange qudits of equal radix."
+        );
+        let dim = radixes.iter().product();
+        PermutationGate { perm, radixes, dim }
+    }
+
+    pub fn permutation(&self) -> &Permutation {
+        &self.perm
+    }
+}
+
+impl Size for PermutationGate {
+    fn num_qudits(&self) -> usize {
+        self.radixes.len()
+    }
+}
+
+impl Unitary for PermutationGate {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn get_utry(&self, _params: &[f64], _const_gates: &[Array2<c64>]) -> Array2<c64> {
+        let mut utry = Array2::zeros((self.dim, self.dim));
+        let mut digits = vec![0; self.radixes.len()];
+        for col in 0..self.dim {
+            let mut rest = col;
+            for (q, &radix) in self.radixes.iter().enumerate().rev() {
+                digits[self.perm.as_slice()[q]] = rest % radix;
+                rest /= radix;
+            }
+            let row = digits
+                .iter()
+                .zip(&self.radixes)
+                .fold(0, |acc, (&digit, &radix)| acc * radix + digit);
+            utry[[row, col]] = r!(1.0);
+        }
+        utry
+    }
+}
+
+impl Gradient for PermutationGate {
+    fn get_grad(&self, _params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
+        Array3::zeros((0, self.dim, self.dim))
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        (
+            self.get_utry(params, const_gates),
+            self.get_grad(params, const_gates),
+        )
+    }
+}
+
+impl Hessian for PermutationGate {}
+
+impl Optimize for PermutationGate {
+    fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        vec![]
+    }
+}
@@ -0,0 +1,143 @@
+use crate::i;
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use std::f64::consts::PI;
+
+use ndarray::{Array1, Array2, Array3, Array4, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// A controlled-phase gate between qudits of arbitrary radixes, applying the
+/// phase `exp(iθab)` to `|a, b>`
+///
+/// On qubits this is the usual controlled-phase gate, and on two qudits of
+/// radix `d` it reduces to the generalized CZ gate at `θ = 2π / d`.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct CPhaseGate {
+    control_radix: usize,
+    target_radix: usize,
+}
+
+impl CPhaseGate {
+    pub fn new(control_radix: usize, target_radix: usize) -> Self {
+        CPhaseGate {
+            control_radix,
+            target_radix,
+        }
+    }
+
+    /// The multiple of `θ` in the phase applied to each basis state.
+    fn exponents(&self) -> Vec<usize> {
+        let d = self.target_radix;
+        (0..self.control_radix * d)
+            .map(|k| (k / d) * (k % d))
+            .collect()
+    }
+}
+
+impl Unitary for CPhaseGate {
+    fn num_params(&self) -> usize {
+        1
+    }
+
+    fn get_utry(&self, params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let phases = Array1::from_iter(
+            self.exponents()
+                .into_iter()
+                .map(|m| i!(params[0] * m as f64).exp()),
+        );
+        Array2::from_diag(&phases)
+    }
+}
+
+impl Gradient for CPhaseGate {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
+        self.get_utry_and_grad(params, const_gates).1
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        let utry = self.get_utry(params, const_gates);
+        let dim = utry.nrows();
+        let mut grad = Array3::zeros((1, dim, dim));
+        for (k, m) in self.exponents().into_iter().enumerate() {
+            grad[[0, k, k]] = i!(m as f64) * utry[[k, k]];
+        }
+        (utry, grad)
+    }
+}
+
+impl Hessian for CPhaseGate {
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        let utry = self.get_utry(params, const_gates);
+        let dim = utry.nrows();
+        let mut hess = Array4::zeros((1, 1, dim, dim));
+        for (k, m) in self.exponents().into_iter().enumerate() {
+            hess[[0, 0, k, k]] = -((m * m) as f64) * utry[[k, k]];
+        }
+        hess
+    }
+}
+
+impl Size for CPhaseGate {
+    fn num_qudits(&self) -> usize {
+        2
+    }
+}
+
+impl Optimize for CPhaseGate {
+    fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        // Re Tr(EU) is the trigonometric polynomial Re Σ_m c_m exp(imθ)
+        let exponents = self.exponents();
+        let degree = *exponents.iter().max().unwrap();
+        let mut coefs = vec![i!(0.0); degree + 1];
+        for (k, &m) in exponents.iter().enumerate() {
+            coefs[m] += env_matrix[[k, k]];
+        }
+        if degree == 1 {
+            return vec![-coefs[1].arg()];
+        }
+        let derivative = |theta: f64, order: i32| -> f64 {
+            coefs
+                .iter()
+                .enumerate()
+                .map(|(m, c)| (c * i!(m as f64).powi(order) * i!(m as f64 * theta).exp()).re)
+                .sum()
+        };
+
+        // Polish every local maximum of a dense sampling with Newton's method
+        // and keep the best, since neighbouring peaks can be nearly equal.
+        let samples = 16 * degree;
+        let grid: Vec<f64> = (0..samples)
+            .map(|k| 2. * PI * k as f64 / samples as f64)
+            .collect();
+        let values: Vec<f64> = grid.iter().map(|&theta| derivative(theta, 0)).collect();
+        let mut best = (f64::NEG_INFINITY, 0.0);
+        for k in 0..samples {
+            let (prev, next) = ((k + samples - 1) % samples, (k + 1) % samples);
+            if values[k] < values[prev] || values[k] < values[next] {
+                continue;
+            }
+            let mut theta = grid[k];
+            for _ in 0..20 {
+                let curvature = derivative(theta, 2);
+                if curvature >= 0. {
+                    break;
+                }
+                let step = derivative(theta, 1) / curvature;
+                theta -= step.clamp(-PI / samples as f64, PI / samples as f64);
+                if step.abs() < 1e-14 {
+                    break;
+                }
+            }
+            let value = derivative(theta, 0);
+            if value > best.0 {
+                best = (value, theta);
+            }
+        }
+        vec![best.1]
+    }
+}
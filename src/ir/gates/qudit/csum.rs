@@ -0,0 +1,67 @@
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+use crate::r;
+
+use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// The qudit controlled-sum gate, mapping `|a, b>` to `|a, a + b mod d>`
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct CSUMGate {
+    radix: usize,
+}
+
+impl CSUMGate {
+    pub fn new(radix: usize) -> Self {
+        CSUMGate { radix }
+    }
+}
+
+impl Unitary for CSUMGate {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn get_utry(&self, _params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let d = self.radix;
+        let mut utry = Array2::zeros((d * d, d * d));
+        for a in 0..d {
+            for b in 0..d {
+                utry[[a * d + (a + b) % d, a * d + b]] = r!(1.0);
+            }
+        }
+        utry
+    }
+}
+
+impl Gradient for CSUMGate {
+    fn get_grad(&self, _params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
+        let dim = self.radix * self.radix;
+        Array3::zeros((0, dim, dim))
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        (
+            self.get_utry(params, const_gates),
+            self.get_grad(params, const_gates),
+        )
+    }
+}
+
+impl Hessian for CSUMGate {}
+
+impl Size for CSUMGate {
+    fn num_qudits(&self) -> usize {
+        2
+    }
+}
+
+impl Optimize for CSUMGate {
+    fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        vec![]
+    }
+}
@@ -0,0 +1,67 @@
+use crate::i;
+use crate::ir::gates::{Gradient, Hessian, Size};
+use crate::ir::gates::{Optimize, Unitary};
+
+use std::f64::consts::PI;
+
+use ndarray::{Array1, Array2, Array3, ArrayViewMut2};
+use ndarray_linalg::c64;
+
+/// The generalized qudit CZ gate, applying the phase `ω^(ab)` to `|a, b>`
+/// where `ω = exp(2πi / d)`
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct CZGate {
+    radix: usize,
+}
+
+impl CZGate {
+    pub fn new(radix: usize) -> Self {
+        CZGate { radix }
+    }
+}
+
+impl Unitary for CZGate {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn get_utry(&self, _params: &[f64], _constant_gates: &[Array2<c64>]) -> Array2<c64> {
+        let d = self.radix;
+        let omega = 2. * PI / d as f64;
+        let phases =
+            Array1::from_shape_fn(d * d, |k| i!(omega * ((k / d) * (k % d) % d) as f64).exp());
+        Array2::from_diag(&phases)
+    }
+}
+
+impl Gradient for CZGate {
+    fn get_grad(&self, _params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
+        let dim = self.radix * self.radix;
+        Array3::zeros((0, dim, dim))
+    }
+
+    fn get_utry_and_grad(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>) {
+        (
+            self.get_utry(params, const_gates),
+            self.get_grad(params, const_gates),
+        )
+    }
+}
+
+impl Hessian for CZGate {}
+
+impl Size for CZGate {
+    fn num_qudits(&self) -> usize {
+        2
+    }
+}
+
+impl Optimize for CZGate {
+    fn optimize(&self, _env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
+        vec![]
+    }
+}
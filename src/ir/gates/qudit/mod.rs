@@ -0,0 +1,65 @@
+mod cphase;
+mod csum;
+mod cz;
+
+pub use cphase::CPhaseGate;
+pub use csum::CSUMGate;
+pub use cz::CZGate;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::{Gate, Unitary};
+    use crate::qis::unitary::UnitaryBuilder;
+
+    use ndarray::Array2;
+    use ndarray_linalg::c64;
+
+    /// The matrix of `gate` acting on the qudits at `location`, built entry by entry.
+    fn embed(gate: &Array2<c64>, radixes: &[usize], location: &[usize]) -> Array2<c64> {
+        let dim: usize = radixes.iter().product();
+        let digits = |mut index: usize| {
+            let mut digits = vec![0; radixes.len()];
+            for (digit, radix) in digits.iter_mut().zip(radixes).rev() {
+                *digit = index % radix;
+                index /= radix;
+            }
+            digits
+        };
+        let local = |digits: &[usize]| {
+            location
+                .iter()
+                .fold(0, |acc, &q| acc * radixes[q] + digits[q])
+        };
+        Array2::from_shape_fn((dim, dim), |(row, col)| {
+            let (row, col) = (digits(row), digits(col));
+            if (0..radixes.len()).all(|q| location.contains(&q) || row[q] == col[q]) {
+                gate[[local(&row), local(&col)]]
+            } else {
+                c64::new(0.0, 0.0)
+            }
+        })
+    }
+
+    #[test]
+    fn builder_embeds_entanglers_on_mixed_radixes() {
+        let radixes = vec![3, 2, 3];
+        let cases: Vec<(Gate, Vec<usize>, Vec<f64>)> = vec![
+            (CSUMGate::new(3).into(), vec![2, 0], vec![]),
+            (CZGate::new(3).into(), vec![0, 2], vec![]),
+            (CPhaseGate::new(2, 3).into(), vec![1, 2], vec![0.37]),
+            (CPhaseGate::new(2, 3).into(), vec![1, 0], vec![-1.2]),
+        ];
+        for (gate, location, params) in cases {
+            let utry = gate.get_utry(&params, &[]);
+            let mut builder = UnitaryBuilder::new(radixes.len(), radixes.clone());
+            builder.apply_right(utry.view(), &location, false);
+            let expected = embed(&utry, &radixes, &location);
+            let error = (builder.get_utry() - expected)
+                .iter()
+                .map(|x| x.norm())
+                .fold(0.0, f64::max);
+            assert!(error < 1e-12, "{:?} on {:?}: {}", gate, location, error);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Helpers shared by the gate unit tests
+
+use std::f64::consts::PI;
+
+use ndarray::{Array2, ArrayView2};
+use ndarray_linalg::{c64, random_using, SVD};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::ir::gates::{Gate, Optimize, Unitary};
+
+pub fn rng(seed: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+pub fn random_params<R: Rng>(num_params: usize, rng: &mut R) -> Vec<f64> {
+    (0..num_params).map(|_| rng.gen_range(-PI..PI)).collect()
+}
+
+/// A random complex matrix with entries in the unit square.
+pub fn random_env<R: Rng>(dim: usize, rng: &mut R) -> Array2<c64> {
+    random_using((dim, dim), rng)
+}
+
+/// `Tr(env @ utry)`
+pub fn trace_with(env: ArrayView2<c64>, utry: ArrayView2<c64>) -> c64 {
+    env.dot(&utry).diag().sum()
+}
+
+/// The sum of the singular values, which is the largest `|Tr(env @ U)|` over
+/// all unitaries `U`.
+pub fn nuclear_norm(env: ArrayView2<c64>) -> f64 {
+    let (_, sigma, _) = env.to_owned().svd(false, false).unwrap();
+    sigma.sum()
+}
+
+/// Maximize `objective` over `num_params` angles by pattern search from many
+/// random starting points.
+pub fn numeric_max<F, R>(objective: F, num_params: usize, rng: &mut R) -> f64
+where
+    F: Fn(&[f64]) -> f64,
+    R: Rng,
+{
+    let mut best = f64::NEG_INFINITY;
+    for _ in 0..40 {
+        let mut point = random_params(num_params, rng);
+        let mut value = objective(&point);
+        let mut step = 0.5;
+        while step > 1e-10 {
+            let mut improved = false;
+            for k in 0..num_params {
+                for sign in [1.0, -1.0] {
+                    let mut trial = point.clone();
+                    trial[k] += sign * step;
+                    let trial_value = objective(&trial);
+                    if trial_value > value {
+                        point = trial;
+                        value = trial_value;
+                        improved = true;
+                    }
+                }
+            }
+            if !improved {
+                step /= 2.0;
+            }
+        }
+        best = best.max(value);
+    }
+    best
+}
+
+/// Assert that `gate.optimize` reaches the maximum of `|Tr(env @ U)|` found by
+/// `numeric_max` on a few random environments.
+pub fn assert_optimize_reaches_numeric_max(gate: &Gate, seed: u64) {
+    let mut rng = rng(seed);
+    let dim = gate.get_utry(&vec![0.0; gate.num_params()], &[]).nrows();
+    for _ in 0..5 {
+        let mut env = random_env(dim, &mut rng);
+        let objective =
+            |params: &[f64]| trace_with(env.view(), gate.get_utry(params, &[]).view()).norm();
+        let expected = numeric_max(objective, gate.num_params(), &mut rng);
+        let params = gate.optimize(env.view_mut());
+        let reached = trace_with(env.view(), gate.get_utry(&params, &[]).view()).norm();
+        assert!(reached > expected - 1e-8, "{} < {}", reached, expected);
+    }
+}
@@ -1,5 +1,7 @@
-use ndarray::{Array2, Array3};
-use ndarray_linalg::c64;
+use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray_linalg::{c64, SVD};
+
+use crate::squaremat::*;
 
 #[inline(always)]
 pub fn rot_x(theta: f64) -> Array2<c64> {
@@ -137,3 +139,17 @@ pub fn rot_z_jac(theta: f64, phase: Option<c64>) -> Array3<c64> {
         }
     }
 }
+
+/// Compute the full SVD of a square matrix, returning `(U, V^†)`.
+pub fn svd(matrix: ArrayViewMut2<c64>) -> (Array2<c64>, Array2<c64>) {
+    let result = matrix.svd(true, true).unwrap();
+    (result.0.unwrap(), result.2.unwrap())
+}
+
+/// Calculate the unitary `U` that maximizes `|Tr(env_matrix @ U)|`.
+///
+/// With `env_matrix = W S V^†`, this is `V W^†`, and the trace is the sum of singular values.
+pub fn optimal_unitary(env_matrix: ArrayViewMut2<c64>) -> Array2<c64> {
+    let (u, vt) = svd(env_matrix);
+    vt.conj().t().matmul(u.conj().t())
+}
@@ -1,7 +1,7 @@
 use crate::{
     ir::circuit::Circuit,
     ir::gates::{Gradient, Unitary},
-    utils::{matrix_distance_squared, matrix_distance_squared_jac, state_infidelity, state_infidelity_jac, matrix_distance_system_squared, matrix_distance_system_squared_jac},
+    utils::{matrix_distance_squared, matrix_distance_squared_trace_jac, state_infidelity, state_infidelity_adjoint_jac, matrix_distance_system_squared, matrix_distance_system_squared_jac},
 };
 
 use enum_dispatch::enum_dispatch;
@@ -55,17 +55,16 @@ impl CostFn for HilbertSchmidtCostFn {
 
 impl DifferentiableCostFn for HilbertSchmidtCostFn {
     fn get_grad(&self, params: &[f64]) -> Vec<f64> {
-        let (m, j) = self
-            .circ
-            .get_utry_and_grad(params, &self.circ.constant_gates);
-        matrix_distance_squared_jac(self.target.view(), m.view(), j.view()).1
+        self.get_cost_and_grad(params).1
     }
 
     fn get_cost_and_grad(&self, params: &[f64]) -> (f64, Vec<f64>) {
-        let (m, j) = self
-            .circ
-            .get_utry_and_grad(params, &self.circ.constant_gates);
-        matrix_distance_squared_jac(self.target.view(), m.view(), j.view())
+        let (trace, d_traces) = self.circ.get_target_trace_and_grads(
+            params,
+            &self.circ.constant_gates,
+            self.target.view(),
+        );
+        matrix_distance_squared_trace_jac(self.target.nrows(), trace, d_traces.view())
     }
 }
 
@@ -94,17 +93,16 @@ impl CostFn for HilbertSchmidtStateCostFn {
 
 impl DifferentiableCostFn for HilbertSchmidtStateCostFn {
     fn get_grad(&self, params: &[f64]) -> Vec<f64> {
-        let (m, j) = self
-            .circ
-            .get_state_and_grads(params, &self.circ.constant_gates);
-        state_infidelity_jac(self.target.view(), m.view(), j.view()).1
+        self.get_cost_and_grad(params).1
     }
 
     fn get_cost_and_grad(&self, params: &[f64]) -> (f64, Vec<f64>) {
-        let (m, j) = self
-            .circ
-            .get_state_and_grads(params, &self.circ.constant_gates);
-        state_infidelity_jac(self.target.view(), m.view(), j.view())
+        let (m, overlaps) = self.circ.get_state_and_adjoint_grads(
+            params,
+            &self.circ.constant_gates,
+            self.target.view(),
+        );
+        state_infidelity_adjoint_jac(self.target.view(), m.view(), overlaps.view())
     }
 }
 
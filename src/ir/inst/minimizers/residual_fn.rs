@@ -117,17 +117,11 @@ impl DifferentiableResidualFn for HilbertSchmidtResidualFn {
 pub struct HilbertSchmidtStateResidualFn {
     circ: Circuit,
     target: Array1<c64>,
-    eye: Array2<f64>,
 }
 
 impl HilbertSchmidtStateResidualFn {
     pub fn new(circ: Circuit, target: Array1<c64>) -> Self {
-        let size = target.shape()[0];
-        HilbertSchmidtStateResidualFn {
-            circ,
-            target,
-            eye: Array2::eye(size),
-        }
+        HilbertSchmidtStateResidualFn { circ, target }
     }
 
     pub fn is_sendable(&self) -> bool {
@@ -153,6 +147,11 @@ impl ResidualFn for HilbertSchmidtStateResidualFn {
     }
 }
 
+// The Jacobian holds every component of every state derivative, so the
+// adjoint method, which yields one `<bra|∂ψ/∂θ>` row per backward sweep, would
+// need one sweep per residual. `get_state_and_grads` carries each derivative
+// forward as a state vector instead, using `O(num_params * dim)` memory, the
+// size of the Jacobian itself.
 impl DifferentiableResidualFn for HilbertSchmidtStateResidualFn {
     fn get_grad(&self, params: &[f64]) -> Array2<f64> {
         let (m, j) = self
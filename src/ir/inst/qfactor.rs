@@ -72,29 +72,25 @@ impl QFactorInstantiator {
     pub fn sweep_circuit(&self, unitary_builder: &mut UnitaryBuilder, circuit: &mut Circuit) {
         // Start by looping backwards
         for op in circuit.ops.iter_mut().rev() {
-            let gate = op.get_utry(&[], &circuit.constant_gates);
-            unitary_builder.apply_right(gate.view(), &op.location, true);
+            op.apply_right(unitary_builder, &[], &circuit.constant_gates, true);
             if op.num_params() != 0 {
                 let mut env = unitary_builder.calc_env_matrix(&op.location);
                 let params = op.optimize(env.view_mut());
                 op.params = params;
             }
-            let gate = op.get_utry(&[], &circuit.constant_gates);
-            unitary_builder.apply_left(gate.view(), &op.location, false);
+            op.apply_left(unitary_builder, &[], &circuit.constant_gates, false);
         }
 
         // reset for new loop through all the gates the opposite order
         for op in circuit.ops.iter_mut() {
-            let gate = op.get_utry(&[], &circuit.constant_gates);
-            unitary_builder.apply_left(gate.view(), &op.location, true);
+            op.apply_left(unitary_builder, &[], &circuit.constant_gates, true);
 
             if op.num_params() != 0 {
                 let mut env = unitary_builder.calc_env_matrix(&op.location);
                 let params = op.optimize(env.view_mut());
                 op.params = params;
             }
-            let gate = op.get_utry(&[], &circuit.constant_gates);
-            unitary_builder.apply_right(gate.view(), &op.location, false);
+            op.apply_right(unitary_builder, &[], &circuit.constant_gates, false);
         }
     }
 }
@@ -106,6 +102,19 @@ impl Instantiate for QFactorInstantiator {
         target: Array2<c64>,
         x0: &[f64],
     ) -> Vec<f64> {
+        if circuit.param_map.is_some() {
+            panic!("QFactor optimizes each operation independently and cannot respect a parameter map");
+        }
+        if let Some(op) = circuit
+            .ops
+            .iter()
+            .find(|op| op.num_params() != 0 && !op.can_optimize())
+        {
+            panic!(
+                "QFactor needs an update for every parameterized gate, but the gate at {:?} has none",
+                op.location
+            );
+        }
         if x0.len() != circuit.num_params() {
             panic!(
                 "Incorrect number of parameters in x0 for the QFactor instantiator, expected {}, got {}",
@@ -152,3 +161,25 @@ impl Instantiate for QFactorInstantiator {
         circuit.get_params()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::{HamiltonianGate, RZGate};
+    use crate::ir::Operation;
+
+    #[test]
+    #[should_panic(expected = "the gate at [0] has none")]
+    fn instantiate_rejects_gates_without_an_update() {
+        let z = Array2::from_diag(&ndarray::arr1(&[c64::new(1.0, 0.0), c64::new(-1.0, 0.0)]));
+        let ops = vec![
+            (0, Operation::new(RZGate::new().into(), vec![0], vec![0.0])),
+            (
+                1,
+                Operation::new(HamiltonianGate::new(1, vec![2], vec![0]).into(), vec![0], vec![0.0]),
+            ),
+        ];
+        let mut circuit = Circuit::new(1, vec![2], ops, vec![z]);
+        QFactorInstantiator::default().instantiate(&mut circuit, Array2::eye(2), &[0.0, 0.0]);
+    }
+}
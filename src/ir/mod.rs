@@ -2,5 +2,9 @@ pub mod operation;
 pub mod circuit;
 pub mod gates;
 pub mod inst;
+pub mod noise;
+pub mod param_map;
 
-pub use operation::Operation;
\ No newline at end of file
+pub use noise::KrausChannel;
+pub use operation::Operation;
+pub use param_map::{AffineExpression, ParameterMap};
\ No newline at end of file
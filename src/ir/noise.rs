@@ -0,0 +1,172 @@
+use ndarray::{Array2, ArrayView2};
+use ndarray_linalg::c64;
+
+use crate::squaremat::*;
+use crate::{i, r};
+
+/// A quantum channel `ρ -> Σ_k K_k ρ K_k^†` given by its Kraus operators
+///
+/// A channel acts on every qudit of the operation carrying it, right after the
+/// operation's gate. Superoperators use row-major vectorization, so that
+/// `vec(ρ)[i * dim + j] = ρ[i, j]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KrausChannel {
+    radixes: Vec<usize>,
+    dim: usize,
+    operators: Vec<Array2<c64>>,
+}
+
+impl KrausChannel {
+    /// Build a channel from custom Kraus operators.
+    ///
+    /// Panics if the operators do not match `radixes` or if `Σ_k K_k^† K_k`
+    /// is not the identity.
+    pub fn new(radixes: Vec<usize>, operators: Vec<Array2<c64>>) -> Self {
+        let dim: usize = radixes.iter().product();
+        if operators.is_empty() {
+            panic!("A Kraus channel needs at least one operator");
+        }
+        let mut completeness = Array2::<c64>::zeros((dim, dim));
+        for op in &operators {
+            if op.dim() != (dim, dim) {
+                panic!(
+                    "Kraus operator has shape {:?}, expected ({}, {})",
+                    op.shape(),
+                    dim,
+                    dim
+                );
+            }
+            completeness += &op.t().mapv(|x| x.conj()).dot(op);
+        }
+        completeness -= &Array2::eye(dim);
+        if completeness.iter().any(|x| x.norm() > 1e-8) {
+            panic!("Kraus operators do not preserve the trace");
+        }
+        KrausChannel {
+            radixes,
+            dim,
+            operators,
+        }
+    }
+
+    /// `ρ -> (1 - p) ρ + p I / dim`, acting jointly on all of the qudits.
+    ///
+    /// The Kraus operators are the `dim^2` generalized Pauli operators
+    /// `X^a Z^b`, which average any state to the maximally mixed one.
+    pub fn depolarizing(radixes: Vec<usize>, p: f64) -> Self {
+        let dim: usize = radixes.iter().product();
+        let weight = p / (dim * dim) as f64;
+        let mut operators = vec![];
+        for a in 0..dim {
+            for b in 0..dim {
+                let scale = if a == 0 && b == 0 {
+                    1.0 - p + weight
+                } else {
+                    weight
+                };
+                operators.push(weyl(dim, a, b) * r!(scale.sqrt()));
+            }
+        }
+        KrausChannel::new(radixes, operators)
+    }
+
+    /// Decay of every excited level to `|0>` with probability `gamma`,
+    /// independently on each qudit.
+    pub fn amplitude_damping(radixes: Vec<usize>, gamma: f64) -> Self {
+        let factors = radixes.iter().map(|&radix| {
+            let mut no_decay = Array2::zeros((radix, radix));
+            no_decay[(0, 0)] = r!(1.0);
+            let mut operators = vec![];
+            for level in 1..radix {
+                no_decay[(level, level)] = r!((1.0 - gamma).sqrt());
+                let mut decay = Array2::zeros((radix, radix));
+                decay[(0, level)] = r!(gamma.sqrt());
+                operators.push(decay);
+            }
+            operators.insert(0, no_decay);
+            operators
+        });
+        KrausChannel::new(radixes.clone(), tensor_products(factors))
+    }
+
+    /// `ρ -> (1 - p) ρ + p diag(ρ)` independently on each qudit, so that the
+    /// off-diagonal elements of each qudit shrink by `1 - p`.
+    pub fn dephasing(radixes: Vec<usize>, p: f64) -> Self {
+        let factors = radixes.iter().map(|&radix| {
+            let weight = p / radix as f64;
+            (0..radix)
+                .map(|b| {
+                    let scale = if b == 0 { 1.0 - p + weight } else { weight };
+                    weyl(radix, 0, b) * r!(scale.sqrt())
+                })
+                .collect()
+        });
+        KrausChannel::new(radixes.clone(), tensor_products(factors))
+    }
+
+    pub fn radixes(&self) -> &[usize] {
+        &self.radixes
+    }
+
+    pub fn num_qudits(&self) -> usize {
+        self.radixes.len()
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn operators(&self) -> &[Array2<c64>] {
+        &self.operators
+    }
+
+    /// The superoperator `Σ_k K_k ⊗ conj(K_k)` acting on `vec(ρ)`.
+    pub fn superoperator(&self) -> Array2<c64> {
+        let mut superop = Array2::zeros((self.dim * self.dim, self.dim * self.dim));
+        for op in &self.operators {
+            superop += &op.kron(&op.mapv(|x| x.conj()));
+        }
+        superop
+    }
+
+    /// The Choi matrix `Σ_ij |i><j| ⊗ E(|i><j|)`.
+    pub fn choi(&self) -> Array2<c64> {
+        superoperator_to_choi(self.superoperator().view())
+    }
+}
+
+/// Reshuffle a row-major superoperator into the Choi matrix
+/// `Σ_ij |i><j| ⊗ E(|i><j|)` of the same channel.
+pub fn superoperator_to_choi(superop: ArrayView2<c64>) -> Array2<c64> {
+    let dim = (superop.nrows() as f64).sqrt().round() as usize;
+    if dim * dim != superop.nrows() || !superop.is_square() {
+        panic!("Superoperator must be square with a perfect square dimension");
+    }
+    Array2::from_shape_fn((dim * dim, dim * dim), |(row, col)| {
+        let (i, a) = (row / dim, row % dim);
+        let (j, b) = (col / dim, col % dim);
+        superop[(a * dim + b, i * dim + j)]
+    })
+}
+
+/// The generalized Pauli operator `X^a Z^b` on a `dim`-level system, where
+/// `X|j> = |j + 1>` and `Z|j> = ω^j |j>`.
+fn weyl(dim: usize, a: usize, b: usize) -> Array2<c64> {
+    let mut op = Array2::zeros((dim, dim));
+    for j in 0..dim {
+        let angle = 2.0 * std::f64::consts::PI * (b * j) as f64 / dim as f64;
+        op[((j + a) % dim, j)] = i!(angle).exp();
+    }
+    op
+}
+
+/// Every Kronecker product that takes one operator from each factor, with the
+/// first factor on the most significant qudit.
+fn tensor_products(factors: impl Iterator<Item = Vec<Array2<c64>>>) -> Vec<Array2<c64>> {
+    factors.fold(vec![Array2::eye(1)], |products, factor| {
+        products
+            .iter()
+            .flat_map(|product| factor.iter().map(move |op| product.kron(op)))
+            .collect()
+    })
+}
@@ -1,13 +1,19 @@
-use ndarray::{Array2, Array3, ArrayViewMut2};
+use ndarray::{Array2, Array3, Array4, ArrayViewMut2};
 use ndarray_linalg::c64;
 
-use super::gates::{Gate, Gradient, Optimize, Unitary};
+use super::gates::{Gate, Gradient, Hessian, Optimize, Unitary};
+use super::noise::KrausChannel;
+use crate::qis::density::DensityMatrixBuilder;
+use crate::qis::state::StateVectorBuilder;
+use crate::qis::unitary::UnitaryBuilder;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Operation {
     pub gate: Gate,
     pub location: Vec<usize>,
     pub params: Vec<f64>,
+    /// Channels applied after the gate in density matrix simulations
+    pub noise: Vec<KrausChannel>,
 }
 
 impl Operation {
@@ -16,6 +22,115 @@ impl Operation {
             gate,
             location,
             params,
+            noise: vec![],
+        }
+    }
+
+    /// Add a channel acting on all of the operation's qudits after its gate.
+    pub fn with_noise(mut self, channel: KrausChannel) -> Self {
+        if channel.num_qudits() != self.location.len() {
+            panic!(
+                "Channel acts on {} qudits but the operation acts on {}",
+                channel.num_qudits(),
+                self.location.len()
+            );
+        }
+        self.noise.push(channel);
+        self
+    }
+
+    /// Apply the operation with `UnitaryBuilder::apply_right`, using the
+    /// builder's cheaper paths for diagonal and permutation gates.
+    pub fn apply_right(
+        &self,
+        builder: &mut UnitaryBuilder,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        inverse: bool,
+    ) {
+        match &self.gate {
+            Gate::Diagonal(d) => {
+                let params = if params.is_empty() {
+                    &self.params
+                } else {
+                    params
+                };
+                builder.apply_diagonal_right(d.get_diagonal(params).view(), &self.location, inverse)
+            }
+            Gate::Permutation(p) => {
+                builder.apply_permutation_right(p.permutation(), &self.location, inverse)
+            }
+            _ => {
+                let utry = self.get_utry(params, const_gates);
+                builder.apply_right(utry.view(), &self.location, inverse)
+            }
+        }
+    }
+
+    /// Apply the operation with `UnitaryBuilder::apply_left`, using the
+    /// builder's cheaper paths for diagonal and permutation gates.
+    pub fn apply_left(
+        &self,
+        builder: &mut UnitaryBuilder,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        inverse: bool,
+    ) {
+        match &self.gate {
+            Gate::Diagonal(d) => {
+                let params = if params.is_empty() {
+                    &self.params
+                } else {
+                    params
+                };
+                builder.apply_diagonal_left(d.get_diagonal(params).view(), &self.location, inverse)
+            }
+            Gate::Permutation(p) => {
+                builder.apply_permutation_left(p.permutation(), &self.location, inverse)
+            }
+            _ => {
+                let utry = self.get_utry(params, const_gates);
+                builder.apply_left(utry.view(), &self.location, inverse)
+            }
+        }
+    }
+
+    /// Apply the operation to a state vector, using the builder's cheaper
+    /// paths for diagonal and permutation gates.
+    pub fn apply_state(
+        &self,
+        builder: &mut StateVectorBuilder,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) {
+        match &self.gate {
+            Gate::Diagonal(d) => {
+                let params = if params.is_empty() {
+                    &self.params
+                } else {
+                    params
+                };
+                builder.apply_diagonal(d.get_diagonal(params).view(), &self.location)
+            }
+            Gate::Permutation(p) => builder.apply_permutation(p.permutation(), &self.location),
+            _ => {
+                let utry = self.get_utry(params, const_gates);
+                builder.apply(utry.view(), &self.location)
+            }
+        }
+    }
+
+    /// Apply the operation and then its noise to a density matrix.
+    pub fn apply_density(
+        &self,
+        builder: &mut DensityMatrixBuilder,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) {
+        self.apply_right(&mut builder.builder, params, const_gates, false);
+        self.apply_left(&mut builder.builder, params, const_gates, true);
+        for channel in &self.noise {
+            builder.apply_channel(channel, &self.location);
         }
     }
 }
@@ -55,8 +170,38 @@ impl Gradient for Operation {
     }
 }
 
+impl Hessian for Operation {
+    fn get_hessian(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array4<c64> {
+        if params.is_empty() {
+            self.gate.get_hessian(&self.params, const_gates)
+        } else {
+            self.gate.get_hessian(params, const_gates)
+        }
+    }
+
+    fn get_utry_grad_and_hessian(
+        &self,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+    ) -> (Array2<c64>, Array3<c64>, Array4<c64>) {
+        if params.is_empty() {
+            self.gate
+                .get_utry_grad_and_hessian(&self.params, const_gates)
+        } else {
+            self.gate.get_utry_grad_and_hessian(params, const_gates)
+        }
+    }
+}
+
 impl Optimize for Operation {
     fn optimize(&self, env_matrix: ArrayViewMut2<c64>) -> Vec<f64> {
-        self.gate.optimize(env_matrix)
+        match &self.gate {
+            Gate::Circuit(c) => c.optimize_from(&self.params, env_matrix),
+            gate => gate.optimize(env_matrix),
+        }
+    }
+
+    fn can_optimize(&self) -> bool {
+        self.gate.can_optimize()
     }
 }
@@ -0,0 +1,105 @@
+use ndarray::{Array, Array4, ArrayView, ArrayView4, Axis, RemoveAxis};
+use ndarray_linalg::c64;
+
+/// An affine expression `offset + Σ coef * x[var]` in a circuit's free variables
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct AffineExpression {
+    pub terms: Vec<(usize, f64)>,
+    pub offset: f64,
+}
+
+impl AffineExpression {
+    pub fn new(terms: Vec<(usize, f64)>, offset: f64) -> Self {
+        AffineExpression { terms, offset }
+    }
+
+    /// The expression that is exactly the variable `var`.
+    pub fn variable(var: usize) -> Self {
+        AffineExpression {
+            terms: vec![(var, 1.0)],
+            offset: 0.0,
+        }
+    }
+
+    pub fn evaluate(&self, vars: &[f64]) -> f64 {
+        self.terms
+            .iter()
+            .fold(self.offset, |acc, &(var, coef)| acc + coef * vars[var])
+    }
+}
+
+/// Maps a circuit's free variables to the concatenated parameters of its
+/// operations, one affine expression per operation parameter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterMap {
+    num_vars: usize,
+    exprs: Vec<AffineExpression>,
+}
+
+impl ParameterMap {
+    pub fn new(num_vars: usize, exprs: Vec<AffineExpression>) -> Self {
+        for expr in &exprs {
+            if let Some(&(var, _)) = expr.terms.iter().find(|(var, _)| *var >= num_vars) {
+                panic!(
+                    "Parameter expression uses variable {}, but there are only {} variables",
+                    var, num_vars
+                );
+            }
+        }
+        ParameterMap { num_vars, exprs }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// The number of operation parameters the map produces.
+    pub fn num_outputs(&self) -> usize {
+        self.exprs.len()
+    }
+
+    /// Evaluate every operation parameter from the free variables.
+    pub fn expand(&self, vars: &[f64]) -> Vec<f64> {
+        self.exprs.iter().map(|expr| expr.evaluate(vars)).collect()
+    }
+
+    /// Apply the chain rule to a gradient with respect to the operation
+    /// parameters, giving the gradient with respect to the free variables.
+    ///
+    /// The leading axis of `grad` indexes the operation parameters, so this
+    /// applies equally to unitary and state vector gradients.
+    pub fn reduce_grad<D: RemoveAxis>(&self, grad: ArrayView<c64, D>) -> Array<c64, D> {
+        let mut shape = grad.raw_dim();
+        shape[0] = self.num_vars;
+        let mut out = Array::zeros(shape);
+        for (expr, d_param) in self.exprs.iter().zip(grad.axis_iter(Axis(0))) {
+            for &(var, coef) in &expr.terms {
+                out.index_axis_mut(Axis(0), var)
+                    .scaled_add(c64::new(coef, 0.0), &d_param);
+            }
+        }
+        out
+    }
+
+    /// Apply the chain rule to a Hessian with respect to the operation
+    /// parameters. The map is affine, so it contributes no curvature of its own.
+    pub fn reduce_hessian(&self, hess: ArrayView4<c64>) -> Array4<c64> {
+        let (_, _, rows, cols) = hess.dim();
+        let mut partial = Array4::zeros((self.num_vars, self.exprs.len(), rows, cols));
+        for (expr, d_param) in self.exprs.iter().zip(hess.axis_iter(Axis(0))) {
+            for &(var, coef) in &expr.terms {
+                partial
+                    .index_axis_mut(Axis(0), var)
+                    .scaled_add(c64::new(coef, 0.0), &d_param);
+            }
+        }
+        let mut out = Array4::zeros((self.num_vars, self.num_vars, rows, cols));
+        for (expr, d_param) in self.exprs.iter().zip(partial.axis_iter(Axis(1))) {
+            for &(var, coef) in &expr.terms {
+                out.index_axis_mut(Axis(1), var)
+                    .scaled_add(c64::new(coef, 0.0), &d_param);
+            }
+        }
+        out
+    }
+}
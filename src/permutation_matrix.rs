@@ -4,6 +4,7 @@ use ndarray::{Array2, ArrayView2};
 use ndarray_linalg::c64;
 use crate::squaremat::*;
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct Permutation {
     perm: Vec<usize>,
 }
@@ -18,6 +19,18 @@ impl Permutation {
         Permutation { perm }
     }
 
+    pub fn as_slice(&self) -> &[usize] {
+        &self.perm
+    }
+
+    pub fn inverse(&self) -> Self {
+        let mut perm = vec![0; self.perm.len()];
+        for (i, &p) in self.perm.iter().enumerate() {
+            perm[p] = i;
+        }
+        Permutation { perm }
+    }
+
     pub fn transpositions(&self) -> Vec<(usize, usize)> {
         let mut res = vec![];
         let a = self.cyclic_form();
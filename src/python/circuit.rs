@@ -1,10 +1,13 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::ir::operation::Operation;
 use crate::ir::circuit::Circuit;
+use crate::ir::{AffineExpression, ParameterMap};
 use crate::ir::gates::Gradient;
 use crate::ir::gates::Unitary;
 use crate::ir::gates::*;
+use crate::permutation_matrix::Permutation;
 
 use ndarray::Array2;
 use ndarray_linalg::c64;
@@ -26,6 +29,11 @@ fn pygate_to_native(pygate: &PyAny, constant_gates: &mut Vec<Array2<c64>>) -> Py
         "CRXGate" => Ok(CRXGate::new().into()),
         "CRYGate" => Ok(CRYGate::new().into()),
         "CRZGate" => Ok(CRZGate::new().into()),
+        "CPGate" => Ok(CPhaseGate::new(2, 2).into()),
+        "CU3Gate" => Ok(CU3Gate::new().into()),
+        "FSIMGate" => Ok(FSimGate::new().into()),
+        "PhasedXZGate" => Ok(PhasedXZGate::new().into()),
+        "XXPlusYYGate" => Ok(XXPlusYYGate::new().into()),
         "RXGate" => Ok(RXGate::new().into()),
         "RYGate" => Ok(RYGate::new().into()),
         "RZGate" => Ok(RZGate::new().into()),
@@ -36,21 +44,64 @@ fn pygate_to_native(pygate: &PyAny, constant_gates: &mut Vec<Array2<c64>>) -> Py
         "U2Gate" => Ok(U2Gate::new().into()),
         "U3Gate" => Ok(U3Gate::new().into()),
         "U8Gate" => Ok(U8Gate::new().into()),
+        "SwapGate" => {
+            let radixes = pygate.getattr("radixes")?.extract::<Vec<usize>>()?;
+            Ok(PermutationGate::new(Permutation::new(vec![1, 0]), radixes).into())
+        },
+        "CSUMGate" | "CZGate" => {
+            let radixes = pygate.getattr("radixes")?.extract::<Vec<usize>>()?;
+            if radixes[0] != radixes[1] {
+                // The native gates act on two qudits of the same radix
+                extract_dynamic_gate(pygate, constant_gates, name)
+            } else if name == "CSUMGate" {
+                Ok(CSUMGate::new(radixes[0]).into())
+            } else {
+                Ok(CZGate::new(radixes[0]).into())
+            }
+        },
         "EmbeddedGate" => {
             let egate = pygate.getattr("gate")?;
             let egate_cls = egate.getattr("__class__")?;
             let egate_dunder_name = egate_cls.getattr("__name__")?;
             let egate_name = egate_dunder_name.extract::<&str>()?;
 
-            if egate_name == "RZGate" {
+            if matches!(egate_name, "RXGate" | "RYGate" | "RZGate") {
                 let level_maps = pygate.getattr("level_maps")?.extract::<Vec<Vec<usize>>>()?;
                 let level1 = level_maps[0][0];
                 let level2 = level_maps[0][1];
                 let radix = pygate.getattr("dim")?.extract::<usize>()?;
-                Ok(RZSubGate::new(radix, level1, level2).into())
+                match egate_name {
+                    "RXGate" => Ok(RXSubGate::new(radix, level1, level2).into()),
+                    "RYGate" => Ok(RYSubGate::new(radix, level1, level2).into()),
+                    _ => Ok(RZSubGate::new(radix, level1, level2).into()),
+                }
             } else {
+                extract_composed_gate(pygate, constant_gates, name, |gate| {
+                    let radixes = pygate.getattr("radixes")?.extract::<Vec<usize>>()?;
+                    let level_maps = pygate.getattr("level_maps")?.extract::<Vec<Vec<usize>>>()?;
+                    Ok(EmbeddedGate::new(gate, radixes, level_maps).into())
+                })
+            }
+        },
+        "ControlledGate" => extract_composed_gate(pygate, constant_gates, name, |gate| {
+            let num_controls = pygate.getattr("num_controls")?.extract::<usize>()?;
+            let control_radixes = pygate.getattr("control_radixes")?.extract::<Vec<usize>>()?;
+            let control_levels = pygate.getattr("control_levels")?.extract::<Vec<Vec<usize>>>()?;
+            Ok(ControlledGate::new(gate, num_controls, control_radixes, control_levels).into())
+        }),
+        "DaggerGate" => extract_composed_gate(pygate, constant_gates, name, |gate| {
+            Ok(DaggerGate::new(gate).into())
+        }),
+        "FrozenParameterGate" => extract_composed_gate(pygate, constant_gates, name, |gate| {
+            let frozen_params = pygate.getattr("frozen_params")?.extract::<HashMap<usize, f64>>()?;
+            Ok(FrozenParameterGate::new(gate, frozen_params.into_iter().collect()).into())
+        }),
+        "CircuitGate" => {
+            if pygate.getattr("num_params")?.extract::<usize>()? == 0 {
                 extract_dynamic_gate(pygate, constant_gates, name)
-                // TODO: Generalize
+            } else {
+                let circuit = pygate.getattr("_circuit")?.extract::<Circuit>()?;
+                Ok(CircuitGate::new(circuit).into())
             }
         },
         "VariableUnitaryGate" => {
@@ -64,6 +115,26 @@ fn pygate_to_native(pygate: &PyAny, constant_gates: &mut Vec<Array2<c64>>) -> Py
     }
 }
 
+/// Lower a gate that wraps `pygate.gate`. Wrappers without parameters become
+/// constant gates, and wrappers of gates with no native form stay dynamic.
+fn extract_composed_gate<F>(
+    pygate: &PyAny,
+    constant_gates: &mut Vec<Array2<c64>>,
+    name: &str,
+    wrap: F,
+) -> PyResult<Gate>
+where
+    F: FnOnce(Gate) -> PyResult<Gate>,
+{
+    if pygate.getattr("num_params")?.extract::<usize>()? == 0 {
+        return extract_dynamic_gate(pygate, constant_gates, name);
+    }
+    match pygate_to_native(pygate.getattr("gate")?, constant_gates)? {
+        Gate::Dynamic(_) => extract_dynamic_gate(pygate, constant_gates, name),
+        gate => wrap(gate),
+    }
+}
+
 fn extract_dynamic_gate(pygate: &PyAny, constant_gates: &mut Vec<Array2<c64>>, name: &str) -> Result<Gate, PyErr> {
     if pygate.getattr("num_params")?.extract::<usize>()? == 0 {
         let args: Vec<f64> = vec![];
@@ -74,10 +145,8 @@ fn extract_dynamic_gate(pygate: &PyAny, constant_gates: &mut Vec<Array2<c64>>, n
         let index = constant_gates.len();
         constant_gates.push(mat);
         Ok(ConstantGate::new(index, gate_size).into())
-    } else if pygate.hasattr("get_unitary")?
-        && ((pygate.hasattr("get_grad")? && pygate.hasattr("get_unitary_and_grad")?)
-            || pygate.hasattr("optimize")?)
-    {
+    } else if pygate.hasattr("get_unitary")? {
+        // Gates without `get_grad` are differentiated numerically by `PyGate`.
         let dynamic: Arc<dyn DynGate + Send + Sync> = Arc::new(PyGate::new(pygate.into()));
         Ok(Gate::Dynamic(dynamic))
     } else {
@@ -109,13 +178,52 @@ impl<'source> FromPyObject<'source> for Circuit {
             let gate = pygate_to_native(pygate, &mut constant_gates)?;
             ops.push((cycle, Operation::new(gate, location, params)));
         }
-        Ok(Circuit::new(
+        let mut circ = Circuit::new(
             size,
             radixes,
             ops,
             constant_gates,
-        ))
+        );
+        if ob.hasattr("param_map")? {
+            let py_map = ob.getattr("param_map")?;
+            if !py_map.is_none() {
+                extract_param_map(py_map, &mut circ)?;
+            }
+        }
+        Ok(circ)
+    }
+}
+
+/// Attach an optional `param_map` of the Python circuit, given as
+/// `(variables, expressions)` with each expression a `(terms, offset)` pair and
+/// `terms` a list of `(variable, coefficient)` pairs.
+fn extract_param_map(py_map: &PyAny, circ: &mut Circuit) -> PyResult<()> {
+    let (variables, exprs) = py_map.extract::<(Vec<f64>, Vec<(Vec<(usize, f64)>, f64)>)>()?;
+    let num_op_params: usize = circ.ops.iter().map(|op| op.num_params()).sum();
+    if exprs.len() != num_op_params {
+        return Err(exceptions::PyValueError::new_err(format!(
+            "Parameter map has {} expressions, but the operations take {} parameters.",
+            exprs.len(),
+            num_op_params
+        )));
+    }
+    if let Some(&(var, _)) = exprs
+        .iter()
+        .flat_map(|(terms, _)| terms)
+        .find(|(var, _)| *var >= variables.len())
+    {
+        return Err(exceptions::PyValueError::new_err(format!(
+            "Parameter map uses variable {}, but there are only {} variables.",
+            var,
+            variables.len()
+        )));
     }
+    let exprs = exprs
+        .into_iter()
+        .map(|(terms, offset)| AffineExpression::new(terms, offset))
+        .collect();
+    circ.set_param_map(ParameterMap::new(variables.len(), exprs), &variables);
+    Ok(())
 }
 
 #[pyclass(name = "Circuit", subclass, unsendable, module = "bqskitrs")]
@@ -5,7 +5,10 @@ use pyo3::prelude::*;
 
 use std::fmt;
 
-use crate::ir::gates::{DynGate, Gradient, Optimize, Size, Unitary};
+use crate::ir::gates::{
+    finite_difference_grad, parameter_shift_grad, DynGate, FallbackGradient, Gradient, Hessian,
+    Optimize, Size, Unitary,
+};
 
 pub struct PyGate {
     gate: PyObject,
@@ -59,9 +62,17 @@ impl Unitary for PyGate {
 }
 
 impl Gradient for PyGate {
-    fn get_grad(&self, params: &[f64], _const_gates: &[Array2<c64>]) -> Array3<c64> {
+    fn get_grad(&self, params: &[f64], const_gates: &[Array2<c64>]) -> Array3<c64> {
         let gil = Python::acquire_gil();
         let py = gil.python();
+        if !self.gate.as_ref(py).hasattr("get_grad").unwrap() {
+            return match self.fallback_gradient() {
+                FallbackGradient::FiniteDifference => {
+                    finite_difference_grad(self, params, const_gates)
+                }
+                FallbackGradient::ParameterShift => parameter_shift_grad(self, params, const_gates),
+            };
+        }
         let args = (PyArray1::from_slice(py, params).to_object(py),);
         let pygrads = self
             .gate
@@ -84,10 +95,21 @@ impl Gradient for PyGate {
     fn get_utry_and_grad(
         &self,
         params: &[f64],
-        _const_gates: &[Array2<c64>],
+        const_gates: &[Array2<c64>],
     ) -> (Array2<c64>, Array3<c64>) {
         let gil = Python::acquire_gil();
         let py = gil.python();
+        if !self
+            .gate
+            .as_ref(py)
+            .hasattr("get_unitary_and_grad")
+            .unwrap()
+        {
+            return (
+                self.get_utry(params, const_gates),
+                self.get_grad(params, const_gates),
+            );
+        }
         let args = (PyArray1::from_slice(py, params).to_object(py),);
         let (pyutry, pygrads) = self
             .gate
@@ -119,8 +141,28 @@ impl Gradient for PyGate {
             grads.into_ref(py).to_owned_array(),
         )
     }
+
+    /// Python gates opt into the parameter-shift rule with a truthy
+    /// `is_parameter_shift_compatible` attribute.
+    fn fallback_gradient(&self) -> FallbackGradient {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let gate = self.gate.as_ref(py);
+        let compatible = gate.hasattr("is_parameter_shift_compatible").unwrap()
+            && gate
+                .getattr("is_parameter_shift_compatible")
+                .and_then(|attr| attr.is_true())
+                .expect("Failed to read is_parameter_shift_compatible on passed gate.");
+        if compatible {
+            FallbackGradient::ParameterShift
+        } else {
+            FallbackGradient::FiniteDifference
+        }
+    }
 }
 
+impl Hessian for PyGate {}
+
 impl Size for PyGate {
     fn num_qudits(&self) -> usize {
         let gil = Python::acquire_gil();
@@ -144,4 +186,13 @@ impl Optimize for PyGate {
             .extract::<Vec<f64>>(py)
             .expect("Failed to convert the return of optimize to a list of floats.")
     }
+
+    fn can_optimize(&self) -> bool {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        self.gate
+            .as_ref(py)
+            .hasattr("optimize")
+            .expect("Failed to check passed gate for optimize.")
+    }
 }
@@ -0,0 +1,70 @@
+use ndarray::{Array2, ArrayView2};
+use ndarray_linalg::c64;
+
+use crate::ir::noise::KrausChannel;
+use crate::qis::unitary::UnitaryBuilder;
+
+/// A type to simulate noisy circuits on a density matrix
+///
+/// The density matrix is held in a `UnitaryBuilder`, whose row indices are
+/// the first `num_qudits` tensor indices and whose column indices are the
+/// rest. A gate `U` is applied as `U ρ U^†` on the rows and columns of its
+/// location, and a channel's superoperator is contracted with the rows and
+/// columns of its location at once.
+pub struct DensityMatrixBuilder {
+    pub num_qudits: usize,
+    pub dim: usize,
+    pub radixes: Vec<usize>,
+    pub builder: UnitaryBuilder,
+}
+
+impl DensityMatrixBuilder {
+    /// Start from the all-zero computational basis state.
+    pub fn new(radixes: Vec<usize>) -> Self {
+        let dim: usize = radixes.iter().product();
+        let mut rho = Array2::zeros((dim, dim));
+        rho[(0, 0)] = c64::new(1.0, 0.0);
+        DensityMatrixBuilder::from_density_matrix(radixes, rho)
+    }
+
+    pub fn from_density_matrix(radixes: Vec<usize>, rho: Array2<c64>) -> Self {
+        let dim = radixes.iter().product();
+        assert_eq!(
+            rho.dim(),
+            (dim, dim),
+            "Density matrix does not match the radixes"
+        );
+        DensityMatrixBuilder {
+            num_qudits: radixes.len(),
+            dim,
+            builder: UnitaryBuilder::from_matrix(radixes.len(), radixes.clone(), rho),
+            radixes,
+        }
+    }
+
+    pub fn get_density_matrix(&mut self) -> Array2<c64> {
+        self.builder.get_utry()
+    }
+
+    /// Apply `U ρ U^†` with `utry` on the qudits in `location`.
+    pub fn apply(&mut self, utry: ArrayView2<c64>, location: &[usize]) {
+        self.builder.apply_right(utry, location, false);
+        self.builder.apply_left(utry, location, true);
+    }
+
+    /// Apply `ρ -> Σ_k K_k ρ K_k^†` on the qudits in `location`.
+    pub fn apply_channel(&mut self, channel: &KrausChannel, location: &[usize]) {
+        let radixes: Vec<usize> = location.iter().map(|&q| self.radixes[q]).collect();
+        if channel.radixes() != &radixes[..] {
+            panic!(
+                "Channel with radixes {:?} cannot act on qudits with radixes {:?}",
+                channel.radixes(),
+                radixes
+            );
+        }
+        let mut idxs = location.to_vec();
+        idxs.extend(location.iter().map(|q| q + self.num_qudits));
+        self.builder
+            .apply_right(channel.superoperator().view(), &idxs, false);
+    }
+}
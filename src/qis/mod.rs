@@ -1 +1,4 @@
+pub mod density;
+pub mod state;
+pub mod trajectory;
 pub mod unitary;
\ No newline at end of file
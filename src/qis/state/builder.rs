@@ -0,0 +1,149 @@
+use ndarray::{Array1, ArrayView1, ArrayView2};
+use ndarray_linalg::c64;
+
+use crate::ir::noise::KrausChannel;
+use crate::permutation_matrix::Permutation;
+
+/// A type to simulate circuits on a state vector, applying one gate at a time
+///
+/// A `k`-qudit gate is applied to each `d^k` block of amplitudes that share the
+/// digits of the other qudits, so it costs `O(dim * d^k)` rather than the
+/// `O(dim^2)` of a dense matrix-vector product or the `O(dim^3)` of building
+/// the full unitary.
+#[derive(Clone, Debug)]
+pub struct StateVectorBuilder {
+    pub num_qudits: usize,
+    pub dim: usize,
+    pub radixes: Vec<usize>,
+    pub state: Array1<c64>,
+    strides: Vec<usize>,
+}
+
+impl StateVectorBuilder {
+    /// Start from the all-zero computational basis state.
+    pub fn new(radixes: Vec<usize>) -> Self {
+        let dim: usize = radixes.iter().product();
+        let mut state = Array1::zeros(dim);
+        state[0] = c64::new(1.0, 0.0);
+        StateVectorBuilder::from_state(radixes, state)
+    }
+
+    pub fn from_state(radixes: Vec<usize>, state: Array1<c64>) -> Self {
+        let dim = radixes.iter().product();
+        assert_eq!(state.len(), dim, "State does not match the radixes");
+        let mut strides = vec![1; radixes.len()];
+        for q in (0..radixes.len().saturating_sub(1)).rev() {
+            strides[q] = strides[q + 1] * radixes[q + 1];
+        }
+        StateVectorBuilder {
+            num_qudits: radixes.len(),
+            dim,
+            radixes,
+            state,
+            strides,
+        }
+    }
+
+    pub fn get_state(&self) -> Array1<c64> {
+        self.state.clone()
+    }
+
+    /// Apply `utry` to the qudits in `location`.
+    pub fn apply(&mut self, utry: ArrayView2<c64>, location: &[usize]) {
+        let local = self.offsets(location, location);
+        let utry: Vec<c64> = utry.iter().copied().collect();
+        let bases = self.offsets_excluding(location);
+        let mut block = vec![c64::new(0.0, 0.0); local.len()];
+        let state = self.state.as_slice_mut().unwrap();
+        for base in bases {
+            for (amp, &offset) in block.iter_mut().zip(&local) {
+                *amp = state[base + offset];
+            }
+            for (row, &offset) in utry.chunks_exact(local.len()).zip(&local) {
+                state[base + offset] = row.iter().zip(&block).map(|(u, amp)| u * amp).sum();
+            }
+        }
+    }
+
+    /// Equivalent to `apply` with `Array2::from_diag(diag)`, but scales the
+    /// state elementwise.
+    pub fn apply_diagonal(&mut self, diag: ArrayView1<c64>, location: &[usize]) {
+        let local = self.offsets(location, location);
+        for base in self.offsets_excluding(location) {
+            for (&d, &offset) in diag.iter().zip(&local) {
+                self.state[base + offset] *= d;
+            }
+        }
+    }
+
+    /// Equivalent to `apply` with a `PermutationGate`'s unitary, but only
+    /// moves amplitudes.
+    pub fn apply_permutation(&mut self, perm: &Permutation, location: &[usize]) {
+        let moved: Vec<usize> = perm.as_slice().iter().map(|&p| location[p]).collect();
+        let local = self.offsets(location, location);
+        let permuted = self.offsets(location, &moved);
+        let mut state = Array1::zeros(self.dim);
+        for base in self.offsets_excluding(location) {
+            for (&from, &to) in local.iter().zip(&permuted) {
+                state[base + to] = self.state[base + from];
+            }
+        }
+        self.state = state;
+    }
+
+    /// Apply one Kraus operator of `channel` to the qudits in `location` and
+    /// renormalize.
+    ///
+    /// Each `K_k` takes the share `||K_k ψ||^2` of `[0, 1)`, and the operator
+    /// whose share contains `sample` is chosen.
+    pub fn apply_kraus(&mut self, channel: &KrausChannel, location: &[usize], sample: f64) {
+        let radixes: Vec<usize> = location.iter().map(|&q| self.radixes[q]).collect();
+        if channel.radixes() != &radixes[..] {
+            panic!(
+                "Channel with radixes {:?} cannot act on qudits with radixes {:?}",
+                channel.radixes(),
+                radixes
+            );
+        }
+        let mut cumulative = 0.0;
+        let mut chosen = None;
+        for op in channel.operators() {
+            let mut branch = self.clone();
+            branch.apply(op.view(), location);
+            let prob: f64 = branch.state.iter().map(|x| x.norm_sqr()).sum();
+            if prob == 0.0 {
+                continue;
+            }
+            cumulative += prob;
+            chosen = Some((branch.state, prob));
+            if sample < cumulative {
+                break;
+            }
+        }
+        let (branch, prob) = chosen.expect("Every Kraus operator annihilates the state");
+        self.state = branch / c64::new(prob.sqrt(), 0.0);
+    }
+
+    /// The offset of every combination of digits on `qudits`, in row-major
+    /// order, placing the digit of `qudits[i]` at the stride of `targets[i]`.
+    fn offsets(&self, qudits: &[usize], targets: &[usize]) -> Vec<usize> {
+        let mut offsets = vec![0];
+        for (&q, &t) in qudits.iter().zip(targets) {
+            let stride = self.strides[t];
+            let mut expanded = Vec::with_capacity(offsets.len() * self.radixes[q]);
+            for offset in offsets {
+                expanded.extend((0..self.radixes[q]).map(|d| offset + d * stride));
+            }
+            offsets = expanded;
+        }
+        offsets
+    }
+
+    /// The offset of every combination of digits on the qudits outside `location`.
+    fn offsets_excluding(&self, location: &[usize]) -> Vec<usize> {
+        let rest: Vec<usize> = (0..self.num_qudits)
+            .filter(|q| !location.contains(q))
+            .collect();
+        self.offsets(&rest, &rest)
+    }
+}
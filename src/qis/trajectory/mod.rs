@@ -0,0 +1,3 @@
+pub mod simulator;
+
+pub use simulator::{TrajectoryEstimate, TrajectorySimulator};
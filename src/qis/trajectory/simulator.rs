@@ -0,0 +1,211 @@
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
+use ndarray_linalg::c64;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::Arc;
+
+use crate::ir::circuit::Circuit;
+use crate::qis::state::StateVectorBuilder;
+use crate::utils::state_dot;
+
+/// The mean of a quantity over trajectories and the standard error of that mean
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct TrajectoryEstimate {
+    pub mean: f64,
+    pub std_error: f64,
+}
+
+/// A Monte-Carlo simulator of noisy circuits on state vectors
+///
+/// Each trajectory samples one Kraus operator per channel with
+/// `Circuit::sample_trajectory`, so memory stays `O(dim)` per thread instead of
+/// the `O(dim^2)` of a density matrix. Trajectory `k` draws from stream `k` of
+/// a generator seeded with `seed`, so results do not depend on the number of
+/// threads.
+#[derive(Clone, Debug)]
+pub struct TrajectorySimulator {
+    pub num_trajectories: usize,
+    pub seed: u64,
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl TrajectorySimulator {
+    /// Run trajectories on a pool of `num_threads` threads, built once here, or
+    /// on rayon's global pool for `None`.
+    pub fn new(num_trajectories: usize, seed: u64, num_threads: Option<usize>) -> Self {
+        if num_trajectories == 0 {
+            panic!("At least one trajectory is needed");
+        }
+        let pool = num_threads.map(|num_threads| {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .expect("Failed to build trajectory thread pool");
+            Arc::new(pool)
+        });
+        TrajectorySimulator {
+            num_trajectories,
+            seed,
+            pool,
+        }
+    }
+
+    /// Simulate trajectory `index` of `circ`.
+    pub fn sample_state(
+        &self,
+        circ: &Circuit,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        index: usize,
+    ) -> Array1<c64> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(index as u64);
+        circ.sample_trajectory(params, const_gates, &mut rng)
+    }
+
+    /// Average `f` of the final state over all trajectories.
+    ///
+    /// Trajectories run on a thread pool, except for circuits that are not
+    /// sendable, which call back into Python and run on the current thread.
+    pub fn average<F>(
+        &self,
+        circ: &Circuit,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        f: F,
+    ) -> TrajectoryEstimate
+    where
+        F: Fn(ArrayView1<c64>) -> f64 + Send + Sync,
+    {
+        let sample = |index| f(self.sample_state(circ, params, const_gates, index).view());
+        let samples: Vec<f64> = if circ.is_sendable() {
+            let run = || {
+                (0..self.num_trajectories)
+                    .into_par_iter()
+                    .map(sample)
+                    .collect()
+            };
+            match &self.pool {
+                Some(pool) => pool.install(run),
+                None => run(),
+            }
+        } else {
+            (0..self.num_trajectories).map(sample).collect()
+        };
+
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let std_error = if samples.len() > 1 {
+            let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            (variance / n).sqrt()
+        } else {
+            0.0
+        };
+        TrajectoryEstimate { mean, std_error }
+    }
+
+    /// Estimate `Tr(O ρ)` for a Hermitian `observable` acting on the qudits in
+    /// `location`.
+    pub fn expectation(
+        &self,
+        circ: &Circuit,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        observable: ArrayView2<c64>,
+        location: &[usize],
+    ) -> TrajectoryEstimate {
+        self.average(circ, params, const_gates, |state| {
+            let mut applied =
+                StateVectorBuilder::from_state(circ.radixes.clone(), state.to_owned());
+            applied.apply(observable, location);
+            state_dot(state, applied.state.view()).re
+        })
+    }
+
+    /// Estimate the fidelity `<t|ρ|t>` of the noisy output with `target`.
+    pub fn fidelity(
+        &self,
+        circ: &Circuit,
+        params: &[f64],
+        const_gates: &[Array2<c64>],
+        target: ArrayView1<c64>,
+    ) -> TrajectoryEstimate {
+        self.average(circ, params, const_gates, |state| {
+            state_dot(target, state).norm_sqr()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::gates::test_utils::*;
+    use crate::ir::gates::{CPhaseGate, SpecialUnitaryGate, U3Gate};
+    use crate::ir::{KrausChannel, Operation};
+
+    fn noisy_circuit() -> Circuit {
+        let mut rng = rng(25);
+        let ops = vec![
+            (
+                0,
+                Operation::new(U3Gate::new().into(), vec![0], random_params(3, &mut rng))
+                    .with_noise(KrausChannel::amplitude_damping(vec![2], 0.1)),
+            ),
+            (
+                0,
+                Operation::new(
+                    SpecialUnitaryGate::new(3).into(),
+                    vec![1],
+                    random_params(8, &mut rng),
+                )
+                .with_noise(KrausChannel::dephasing(vec![3], 0.2)),
+            ),
+            (
+                1,
+                Operation::new(CPhaseGate::new(2, 3).into(), vec![0, 1], vec![1.3])
+                    .with_noise(KrausChannel::depolarizing(vec![2, 3], 0.1)),
+            ),
+        ];
+        Circuit::new(2, vec![2, 3], ops, vec![])
+    }
+
+    #[test]
+    fn fidelity_agrees_with_density_matrix() {
+        let circ = noisy_circuit();
+        let params = circ.get_params();
+        let target = circ.get_state(&params, &[]);
+        let rho = circ.get_density_matrix(&params, &[]);
+        let exact = state_dot(target.view(), rho.dot(&target).view()).re;
+        let estimate =
+            TrajectorySimulator::new(4000, 3, None).fidelity(&circ, &params, &[], target.view());
+        assert!(estimate.std_error > 0.0);
+        assert!(
+            (estimate.mean - exact).abs() < 4.0 * estimate.std_error,
+            "{:?} vs {}",
+            estimate,
+            exact
+        );
+    }
+
+    #[test]
+    fn estimate_does_not_depend_on_threads() {
+        let circ = noisy_circuit();
+        let params = circ.get_params();
+        let target = circ.get_state(&params, &[]);
+        let estimates: Vec<TrajectoryEstimate> = [Some(1), Some(3), None]
+            .iter()
+            .map(|&num_threads| {
+                TrajectorySimulator::new(200, 11, num_threads).fidelity(
+                    &circ,
+                    &params,
+                    &[],
+                    target.view(),
+                )
+            })
+            .collect();
+        assert_eq!(estimates[0], estimates[1]);
+        assert_eq!(estimates[0], estimates[2]);
+    }
+}
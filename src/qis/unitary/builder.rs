@@ -1,6 +1,7 @@
-use ndarray::{Array2, ArrayD, ArrayView2, Ix2};
+use ndarray::{Array2, ArrayD, ArrayView1, ArrayView2, Axis, Ix2};
 use ndarray_linalg::c64;
 
+use crate::permutation_matrix::Permutation;
 use crate::utils::{argsort, trace};
 use crate::squaremat::*;
 use itertools::Itertools;
@@ -17,11 +18,22 @@ pub struct UnitaryBuilder {
 
 impl UnitaryBuilder {
     pub fn new(num_qudits: usize, radixes: Vec<usize>) -> Self {
+        let dim = radixes.iter().product();
+        UnitaryBuilder::from_matrix(num_qudits, radixes, Array2::eye(dim))
+    }
+
+    /// Start from an arbitrary `dim x dim` matrix instead of the identity.
+    pub fn from_matrix(num_qudits: usize, radixes: Vec<usize>, matrix: Array2<c64>) -> Self {
         let dim = radixes.iter().product();
         let num_idxs = num_qudits * 2;
         let pi = Vec::from_iter(0..num_idxs);
-        let mut tensor = Array2::<c64>::eye(dim).into_dyn();
-        tensor = tensor
+        let matrix = if matrix.is_standard_layout() {
+            matrix
+        } else {
+            matrix.as_standard_layout().into_owned()
+        };
+        let tensor = matrix
+            .into_dyn()
             .into_shape([&radixes[..], &radixes[..]].concat())
             .unwrap();
         UnitaryBuilder {
@@ -132,6 +144,95 @@ impl UnitaryBuilder {
         self.tensor = Some(reshape_back.to_owned());
     }
 
+    /// Equivalent to `apply_right` with `Array2::from_diag(diag)`, but scales
+    /// the tensor elementwise instead of performing a dense matmul.
+    pub fn apply_diagonal_right(&mut self, diag: ArrayView1<c64>, location: &[usize], inverse: bool) {
+        // Permute Tensor Indicies
+        let left_perm = location.iter();
+        let right_perm = (0..self.num_idxs).filter(|x| !location.contains(x));
+        let mut perm = vec![];
+        perm.extend(left_perm);
+        perm.extend(right_perm);
+        self.permute_idxs(perm);
+
+        // Reshape
+        let owned_tensor = self.tensor.take().unwrap();
+        let shape = self.get_current_shape();
+        let left_dim: usize = shape[..location.len()].iter().product();
+        let mut reshaped = owned_tensor
+            .to_shape((left_dim, self.dim * self.dim / left_dim))
+            .expect("Cannot reshape tensor to matrix")
+            .into_owned();
+
+        // Scale each row by its diagonal entry
+        for (mut row, &d) in reshaped.outer_iter_mut().zip(diag.iter()) {
+            row *= if inverse { d.conj() } else { d };
+        }
+        let reshape_back = reshaped
+            .to_shape(shape)
+            .expect("Failed to reshape matrix product back");
+        self.tensor = Some(reshape_back.into_owned());
+    }
+
+    /// Equivalent to `apply_left` with `Array2::from_diag(diag)`, but scales
+    /// the tensor elementwise instead of performing a dense matmul.
+    pub fn apply_diagonal_left(&mut self, diag: ArrayView1<c64>, location: &[usize], inverse: bool) {
+        // Permute Tensor Indicies
+        let right_perm: Vec<usize> = location.iter().map(|x| x + self.num_qudits).collect();
+        let left_perm = (0..self.num_idxs).filter(|x| !right_perm.contains(x));
+        let mut perm = vec![];
+        perm.extend(left_perm);
+        perm.extend(right_perm);
+        self.permute_idxs(perm);
+
+        // Reshape
+        let owned_tensor = self.tensor.take().unwrap();
+        let shape = self.get_current_shape();
+        let right_dim: usize = shape[shape.len()-location.len()..].iter().product();
+        let mut reshaped = owned_tensor
+            .to_shape((self.dim * self.dim / right_dim, right_dim))
+            .expect("Cannot reshape tensor to matrix")
+            .into_owned();
+
+        // Scale each column by its diagonal entry
+        for (mut col, &d) in reshaped.axis_iter_mut(Axis(1)).zip(diag.iter()) {
+            col *= if inverse { d.conj() } else { d };
+        }
+        let reshape_back = reshaped
+            .to_shape(shape)
+            .expect("Failed to reshape matrix product back");
+        self.tensor = Some(reshape_back.into_owned());
+    }
+
+    /// Equivalent to `apply_right` with a `PermutationGate`'s unitary, but only
+    /// relabels the output indices of the tensor.
+    pub fn apply_permutation_right(&mut self, perm: &Permutation, location: &[usize], inverse: bool) {
+        let perm = if inverse { perm.inverse() } else { perm.clone() };
+        let relabel: Vec<(usize, usize)> = perm.as_slice().iter()
+            .enumerate()
+            .map(|(i, &p)| (location[i], location[p]))
+            .collect();
+        self.relabel_idxs(&relabel);
+    }
+
+    /// Equivalent to `apply_left` with a `PermutationGate`'s unitary, but only
+    /// relabels the input indices of the tensor.
+    pub fn apply_permutation_left(&mut self, perm: &Permutation, location: &[usize], inverse: bool) {
+        let perm = if inverse { perm.inverse() } else { perm.clone() };
+        let relabel: Vec<(usize, usize)> = perm.as_slice().iter()
+            .enumerate()
+            .map(|(i, &p)| (location[p] + self.num_qudits, location[i] + self.num_qudits))
+            .collect();
+        self.relabel_idxs(&relabel);
+    }
+
+    /// Rename tensor indices without moving any data, given `(old, new)` pairs.
+    fn relabel_idxs(&mut self, relabel: &[(usize, usize)]) {
+        self.pi = self.pi.iter()
+            .map(|&x| relabel.iter().find(|(old, _)| *old == x).map_or(x, |&(_, new)| new))
+            .collect();
+    }
+
     pub fn calc_env_matrix(&mut self, location: &[usize]) -> Array2<c64> {
         self.reset_idxs();
         let mut left_perm: Vec<usize> = (0..self.num_qudits).filter(|x| !location.contains(x)).collect();
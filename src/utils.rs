@@ -51,6 +51,15 @@ pub fn state_infidelity_jac(u: ArrayView1<c64>, m: ArrayView1<c64>, j: ArrayView
     (infidelity, d_infidelity)
 }
 
+/// Like `state_infidelity_jac`, but from the overlaps `<u|dm/dθ_k>` computed
+/// by the adjoint method instead of the full state derivatives.
+pub fn state_infidelity_adjoint_jac(u: ArrayView1<c64>, m: ArrayView1<c64>, overlaps: ArrayView1<c64>) -> (f64, Vec<f64>) {
+    let d = state_dot(u, m);
+    let infidelity = 1.0 - d.norm().powi(2);
+    let d_infidelity = overlaps.iter().map(|dd| -2.0 * (d.re * dd.re + d.im * dd.im)).collect();
+    (infidelity, d_infidelity)
+}
+
 pub fn state_residuals_jac(u: ArrayView1<c64>, m: ArrayView1<c64>, j: ArrayView2<c64>) -> Array2<f64> {
     let d: Vec<c64> = u.iter().zip(m.iter()).map(|(&x, &y)| x - y).collect();
     let mut out = Array2::zeros((j.shape()[0], u.shape()[0]));
@@ -94,6 +103,24 @@ pub fn matrix_distance_squared_jac(
     (dsq, jacs)
 }
 
+/// Like `matrix_distance_squared_jac`, but from `Tr(U^† M)` and
+/// `Tr(U^† dM/dθ_k)` instead of the full unitary and its gradient.
+pub fn matrix_distance_squared_trace_jac(
+    size: usize,
+    trace: c64,
+    d_traces: ArrayView1<c64>,
+) -> (f64, Vec<f64>) {
+    let dsq = 1f64 - trace.norm() / size as f64;
+    if trace.norm() == 0.0 {
+        return (dsq, vec![f64::INFINITY; d_traces.len()]);
+    }
+    let jacs = d_traces
+        .iter()
+        .map(|d| -(d.re * trace.re + d.im * trace.im) / (size as f64 * trace.norm()))
+        .collect();
+    (dsq, jacs)
+}
+
 pub fn matrix_distance_system_squared(a: ArrayView2<c64>, b: ArrayView2<c64>, vec_count: u32) -> f64 {
     // 1 - np.abs(np.trace(np.dot(A,B.H))) / A.shape[0]
     // converted to